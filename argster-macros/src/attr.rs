@@ -0,0 +1,225 @@
+use crate::naming::RenameRule;
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{meta::ParseNestedMeta, Attribute, Expr, LitStr, Path, Visibility};
+
+/// Options given to the impl block through `#[command(...)]`
+#[derive(Default)]
+pub struct ImplOptions {
+    /// How method and parameter names are turned into command line names
+    pub rename_all: RenameRule,
+    /// The name of the app shown in help, defaults to the package name
+    pub name: Option<Expr>,
+    /// The version shown in help, defaults to the package version
+    pub version: Option<Expr>,
+    /// The one line description shown in help, defaults to the package description
+    pub about: Option<Expr>,
+    /// The git commit shown by `version --verbose`
+    pub commit: Option<Expr>,
+    /// The build date shown by `version --verbose`
+    pub build_date: Option<Expr>,
+    /// Lays out the help, defaults to `argster::help::DefaultRenderer`
+    pub renderer: Option<Expr>,
+    /// The styles of the help, defaults to `argster::style::Theme::default()`
+    pub theme: Option<Expr>,
+    /// How much help is shown with a usage error, defaults to
+    /// `argster::help::ErrorHelp::Compact`
+    pub error_help: Option<Expr>,
+    /// The visibility of the invocation enum and the generated entry points,
+    /// private by default
+    pub vis: Option<Visibility>,
+    /// Derived for the invocation enum, none by default since every parameter
+    /// type would need to implement them
+    pub derives: Vec<Path>,
+}
+
+impl ImplOptions {
+    pub fn app_name(&self) -> TokenStream {
+        self.name
+            .as_ref()
+            .map_or_else(|| quote!(env!("CARGO_PKG_NAME")), |name| quote!(#name))
+    }
+
+    pub fn app_version(&self) -> TokenStream {
+        self.version
+            .as_ref()
+            .map_or_else(|| quote!(env!("CARGO_PKG_VERSION")), |version| quote!(#version))
+    }
+
+    pub fn app_about(&self) -> TokenStream {
+        self.about
+            .as_ref()
+            .map_or_else(|| quote!(env!("CARGO_PKG_DESCRIPTION")), |about| quote!(#about))
+    }
+
+    pub fn help_renderer(&self) -> TokenStream {
+        self.renderer.as_ref().map_or_else(
+            || quote!(<::argster::help::DefaultRenderer as ::std::default::Default>::default()),
+            |renderer| quote!(#renderer),
+        )
+    }
+
+    pub fn visibility(&self) -> TokenStream {
+        self.vis.as_ref().map_or_else(TokenStream::new, |vis| quote!(#vis))
+    }
+
+    pub fn error_help(&self) -> TokenStream {
+        self.error_help.as_ref().map_or_else(
+            || quote!(::argster::help::ErrorHelp::Compact),
+            |error_help| quote!(#error_help),
+        )
+    }
+
+    /// The theme given in code, with the overrides from the environment on top
+    pub fn help_theme(&self) -> TokenStream {
+        let theme = self.theme.as_ref().map_or_else(
+            || quote!(<::argster::style::Theme as ::std::default::Default>::default()),
+            |theme| quote!(#theme),
+        );
+        quote!(::argster::style::Theme::with_env_overrides(#theme))
+    }
+
+    pub fn parse(&mut self, meta: ParseNestedMeta) -> syn::Result<()> {
+        if meta.path.is_ident("name") {
+            self.name = Some(meta.value()?.parse()?);
+            Ok(())
+        } else if meta.path.is_ident("version") {
+            self.version = Some(meta.value()?.parse()?);
+            Ok(())
+        } else if meta.path.is_ident("about") {
+            self.about = Some(meta.value()?.parse()?);
+            Ok(())
+        } else if meta.path.is_ident("commit") {
+            self.commit = Some(meta.value()?.parse()?);
+            Ok(())
+        } else if meta.path.is_ident("build_date") {
+            self.build_date = Some(meta.value()?.parse()?);
+            Ok(())
+        } else if meta.path.is_ident("renderer") {
+            self.renderer = Some(meta.value()?.parse()?);
+            Ok(())
+        } else if meta.path.is_ident("theme") {
+            self.theme = Some(meta.value()?.parse()?);
+            Ok(())
+        } else if meta.path.is_ident("error_help") {
+            self.error_help = Some(meta.value()?.parse()?);
+            Ok(())
+        } else if meta.path.is_ident("vis") {
+            self.vis = Some(meta.value()?.parse()?);
+            Ok(())
+        } else if meta.path.is_ident("derive") {
+            meta.parse_nested_meta(|derive| {
+                self.derives.push(derive.path);
+                Ok(())
+            })
+        } else if meta.path.is_ident("rename_all") {
+            let rule = meta.value()?.parse::<LitStr>()?;
+            self.rename_all = rule
+                .value()
+                .parse()
+                .map_err(|ex: String| syn::Error::new(rule.span(), ex))?;
+            Ok(())
+        } else {
+            Err(meta.error(
+                "unknown command option, expected `name`, `version`, `about`, `commit`, `build_date`, `renderer`, `theme`, `error_help`, `vis`, `derive` or `rename_all`",
+            ))
+        }
+    }
+}
+
+/// Options given to a method through `#[command(...)]`
+#[derive(Default, Debug)]
+pub struct MethodOptions {
+    /// The method is a helper rather than a command
+    pub skip: bool,
+    /// The name of the command, instead of the one derived from the method name
+    pub rename: Option<String>,
+    /// The heading the command is listed under in the help
+    pub category: Option<String>,
+}
+
+impl MethodOptions {
+    fn parse(&mut self, meta: ParseNestedMeta) -> syn::Result<()> {
+        if meta.path.is_ident("skip") {
+            self.skip = true;
+            Ok(())
+        } else if meta.path.is_ident("rename") {
+            self.rename = Some(meta.value()?.parse::<LitStr>()?.value());
+            Ok(())
+        } else if meta.path.is_ident("category") {
+            self.category = Some(meta.value()?.parse::<LitStr>()?.value());
+            Ok(())
+        } else {
+            Err(meta.error("unknown command option, expected `skip`, `rename` or `category`"))
+        }
+    }
+}
+
+/// Options given to a parameter through `#[command(...)]`
+#[derive(Default, Debug)]
+pub struct ParamOptions {
+    /// The name of the option, instead of the one derived from the parameter name
+    pub rename: Option<String>,
+    /// The heading the option is listed under in the command's help
+    pub section: Option<String>,
+    /// The value used when the option isn't given, parsed like a value on the command line
+    pub default: Option<String>,
+    /// The environment variable read when the option isn't given, before the default
+    pub env: Option<String>,
+}
+
+impl ParamOptions {
+    fn parse(&mut self, meta: ParseNestedMeta) -> syn::Result<()> {
+        if meta.path.is_ident("rename") {
+            self.rename = Some(meta.value()?.parse::<LitStr>()?.value());
+            Ok(())
+        } else if meta.path.is_ident("section") {
+            self.section = Some(meta.value()?.parse::<LitStr>()?.value());
+            Ok(())
+        } else if meta.path.is_ident("default") {
+            self.default = Some(meta.value()?.parse::<LitStr>()?.value());
+            Ok(())
+        } else if meta.path.is_ident("env") {
+            self.env = Some(meta.value()?.parse::<LitStr>()?.value());
+            Ok(())
+        } else {
+            Err(meta.error("unknown command option, expected `rename`, `section`, `default` or `env`"))
+        }
+    }
+}
+
+/// Removes the `#[command(...)]` attributes, feeding each of their options to `parse`
+fn take_options(
+    attrs: &mut Vec<Attribute>,
+    mut parse: impl FnMut(ParseNestedMeta) -> syn::Result<()>,
+) -> Result<(), TokenStream> {
+    let mut result = Ok(());
+
+    attrs.retain(|attr| {
+        if !attr.path().is_ident("command") {
+            return true;
+        }
+
+        if result.is_ok() {
+            result = attr.parse_nested_meta(&mut parse);
+        }
+
+        false
+    });
+
+    result.map_err(|ex| ex.to_compile_error())
+}
+
+/// Removes the `#[command(...)]` attributes from a method, returning the options they set
+pub fn take_method_options(attrs: &mut Vec<Attribute>) -> Result<MethodOptions, TokenStream> {
+    let mut options = MethodOptions::default();
+    take_options(attrs, |meta| options.parse(meta))?;
+    Ok(options)
+}
+
+/// Removes the `#[command(...)]` attributes from a parameter, returning the options they set
+pub fn take_param_options(attrs: &mut Vec<Attribute>) -> Result<ParamOptions, TokenStream> {
+    let mut options = ParamOptions::default();
+    take_options(attrs, |meta| options.parse(meta))?;
+    Ok(options)
+}
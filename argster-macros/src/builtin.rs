@@ -0,0 +1,201 @@
+use crate::doc::DocData;
+use proc_macro2::{Span, TokenStream};
+use quote::{quote, quote_spanned};
+
+/// Commands that argster handles itself, a method can't use their names
+pub const BUILTIN_COMMANDS: [&str; 2] = ["help", "version"];
+
+/// An error if a command uses the name of a built in command, which would
+/// otherwise shadow it
+pub fn check_command_name(name: &str, span: Span) -> Result<(), TokenStream> {
+    if !BUILTIN_COMMANDS.contains(&name) {
+        return Ok(());
+    }
+
+    let err = format!(
+        "the command {name} conflicts with the built in {name} command, please rename it with #[command(rename = \"..\")]"
+    );
+    Err(quote_spanned!(span => compile_error!(#err);))
+}
+
+/// Flags that argster handles itself for every command
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BuiltinFlag {
+    Help,
+    Version,
+    Color,
+    NoPager,
+}
+
+impl BuiltinFlag {
+    pub const ALL: [BuiltinFlag; 4] = [
+        BuiltinFlag::Help,
+        BuiltinFlag::Version,
+        BuiltinFlag::Color,
+        BuiltinFlag::NoPager,
+    ];
+
+    pub fn long(self) -> &'static str {
+        match self {
+            BuiltinFlag::Help => "help",
+            BuiltinFlag::Version => "version",
+            BuiltinFlag::Color => "color",
+            BuiltinFlag::NoPager => "no-pager",
+        }
+    }
+
+    pub fn short(self) -> Option<&'static str> {
+        match self {
+            BuiltinFlag::Help => Some("h"),
+            BuiltinFlag::Version => Some("V"),
+            BuiltinFlag::Color | BuiltinFlag::NoPager => None,
+        }
+    }
+
+    /// Whether a parameter using the name of the flag is an error, otherwise
+    /// the parameter takes priority and the flag isn't available for the command
+    pub fn is_reserved(self) -> bool {
+        match self {
+            BuiltinFlag::Help => true,
+            // Global flags are taken before the command, so they can't clash with its parameters
+            BuiltinFlag::Version | BuiltinFlag::Color | BuiltinFlag::NoPager => false,
+        }
+    }
+
+    /// Whether the flag is only taken before the command, where it configures
+    /// the output rather than the command
+    pub fn is_global(self) -> bool {
+        match self {
+            BuiltinFlag::Help | BuiltinFlag::Version => false,
+            BuiltinFlag::Color | BuiltinFlag::NoPager => true,
+        }
+    }
+
+    /// The `--long/-s` names of the flag, for errors
+    fn names(self) -> String {
+        match self.short() {
+            Some(short) => format!("--{}/-{short}", self.long()),
+            None => format!("--{}", self.long()),
+        }
+    }
+
+    /// Checks the parsed args for the flag, returning early from `__argster_parse` if it is present
+    fn check(self, command: &str) -> TokenStream {
+        let long = self.long();
+        let short = self.short().unwrap_or_default();
+
+        match self {
+            // The long flag shows the long help, the short one the short help
+            BuiltinFlag::Help => quote!(
+                if args.contains_key(#long) || args.contains_key(#short) {
+                    return Err((Some(#command), ::argster::from_args::Error::Help {
+                        topic: ::argster::help::Topic::Command(#command.to_string()),
+                        long: args.contains_key(#long),
+                    }));
+                }
+            ),
+            BuiltinFlag::Version => quote!(
+                if args.contains_key(#long) || args.contains_key(#short) {
+                    return Err((Some(#command), ::argster::from_args::Error::Version { verbose: false }));
+                }
+            ),
+            // Global flags are taken before the command by `__argster_global_flags`
+            BuiltinFlag::Color | BuiltinFlag::NoPager => quote!(),
+        }
+    }
+
+    /// Takes the global flag from the front of the args in `__argster_global_flags`,
+    /// where `value` is the part after a `=`
+    fn take(self) -> TokenStream {
+        let long = self.long();
+
+        match self {
+            BuiltinFlag::Color => quote!(
+                let value = value.or_else(|| {
+                    taken += 1;
+                    args.get(taken).cloned()
+                });
+                let item = value.map(::argster::ArgsItem::String);
+                let choice = ::argster::from_args::FromArgsItem::from_args_item(item.as_ref()).map_err(|x| (None, x))?;
+                flags.color = Some(choice);
+            ),
+            // `--no-pager=false` is accepted like any other flag
+            BuiltinFlag::NoPager => quote!(
+                let item = value.map_or(::argster::ArgsItem::Present, ::argster::ArgsItem::String);
+                flags.no_pager = ::argster::from_args::FromArgsItem::from_args_item(Some(&item)).map_err(|x| (None, x.with_name(#long)))?;
+            ),
+            BuiltinFlag::Help | BuiltinFlag::Version => unreachable!("--{long} isn't a global flag"),
+        }
+    }
+}
+
+/// The long and short names of the flags every command takes, global flags
+/// are taken before the command instead
+pub fn command_flag_names() -> Vec<&'static str> {
+    BuiltinFlag::ALL
+        .into_iter()
+        .filter(|flag| !flag.is_global())
+        .flat_map(|flag| std::iter::once(flag.long()).chain(flag.short()))
+        .collect()
+}
+
+/// Generates `__argster_global_flags`, which splits the global flags at the
+/// front of the args from the command line that follows them
+pub fn global_flags() -> TokenStream {
+    let arms = BuiltinFlag::ALL.into_iter().filter(|flag| flag.is_global()).map(|flag| {
+        let long = format!("--{}", flag.long());
+        let take = flag.take();
+        quote!(#long => { #take })
+    });
+
+    quote!(
+        fn __argster_global_flags(args: impl IntoIterator<Item = impl Into<String>>) -> Result<(::argster::term::GlobalFlags, Vec<String>), (Option<&'static str>, ::argster::from_args::Error)> {
+            let mut args = args.into_iter().map(Into::into).collect::<Vec<String>>();
+            let mut flags = ::argster::term::GlobalFlags::default();
+            let mut taken = 0;
+
+            while let Some(arg) = args.get(taken) {
+                let (name, value) = match arg.split_once('=') {
+                    Some((name, value)) => (name, Some(value.to_string())),
+                    None => (arg.as_str(), None),
+                };
+
+                match name {
+                    #(#arms)*
+                    _ => break,
+                }
+                taken += 1;
+            }
+
+            args.drain(..taken.min(args.len()));
+            Ok((flags, args))
+        }
+    )
+}
+
+/// Generates the checks for the built in flags of a command, or an error if
+/// one of its parameters, given with their spans, conflicts with a reserved flag
+pub fn builtin_checks(command: &str, params: &[(DocData, Span)]) -> Result<TokenStream, TokenStream> {
+    let mut checks = TokenStream::new();
+
+    for flag in BuiltinFlag::ALL {
+        let conflict = params.iter().find(|(param, _)| {
+            param.long == flag.long() || (flag.short().is_some() && param.short.as_deref() == flag.short())
+        });
+
+        match conflict {
+            Some((param, span)) if flag.is_reserved() => {
+                let err = format!(
+                    "--{} conflicts with the built in {} flag of {command}, please rename it",
+                    param.long,
+                    flag.names()
+                );
+                return Err(quote_spanned!(*span => compile_error!(#err);));
+            }
+            Some(_) => (),
+            None => checks.extend(flag.check(command)),
+        }
+    }
+
+    Ok(checks)
+}
@@ -8,6 +8,14 @@ pub struct DocData {
     pub long: String,
     pub short: Option<String>,
     pub docs: String,
+    /// Further lines describing the arg, only shown in the long help
+    pub details: String,
+    /// The heading the arg is listed under, set with `#[command(section = "..")]`
+    pub section: Option<String>,
+    /// Set with `#[command(default = "..")]`
+    pub default: Option<String>,
+    /// Set with `#[command(env = "..")]`
+    pub env: Option<String>,
 }
 
 impl FromStr for DocData {
@@ -52,6 +60,7 @@ impl FromStr for DocData {
                     long,
                     short: Some(short.to_string()),
                     docs: docs.to_string(),
+                    ..Default::default()
                 })
             } else {
                 Ok(DocData {
@@ -70,29 +79,223 @@ impl FromStr for DocData {
     }
 }
 
-pub fn parse_docs(attrs: &Vec<Attribute>) -> Result<HashMap<String, DocData>, TokenStream> {
+/// The lines of an item's doc comment
+fn doc_lines(attrs: &[Attribute]) -> Vec<String> {
     attrs
         .iter()
         .filter_map(|item| {
-            item.meta
-                .require_name_value()
-                .ok()
-                .and_then(|x: &syn::MetaNameValue| {
-                    Some((
-                        x.path.require_ident().ok()?.to_string(),
-                        match x.value {
-                            Expr::Lit(ExprLit {
-                                lit: Lit::Str(ref s),
-                                ..
-                            }) => s.value().trim().to_string(),
-                            _ => return None,
-                        },
-                    ))
-                })
+            let item = item.meta.require_name_value().ok()?;
+            if !item.path.is_ident("doc") {
+                return None;
+            }
+
+            match item.value {
+                Expr::Lit(ExprLit {
+                    lit: Lit::Str(ref s),
+                    ..
+                }) => Some(s.value()),
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+/// Splits doc lines into the description before the first `# Heading`, and
+/// the lines of each section, headings inside code blocks are ignored
+fn split_sections(lines: Vec<String>) -> (Vec<String>, Vec<(String, Vec<String>)>) {
+    let mut description = vec![];
+    let mut sections: Vec<(String, Vec<String>)> = vec![];
+    let mut in_code = false;
+
+    for line in lines {
+        let trimmed = line.trim();
+        if trimmed.starts_with("```") {
+            in_code = !in_code;
+        }
+
+        match trimmed.strip_prefix("# ") {
+            Some(heading) if !in_code => sections.push((heading.trim().to_string(), vec![])),
+            _ => match sections.last_mut() {
+                Some((_, section)) => section.push(line),
+                None => description.push(line),
+            },
+        }
+    }
+
+    (description, sections)
+}
+
+/// A `# Heading` section of a command's docs, other than `# Args`
+#[derive(Clone, Debug)]
+pub enum Section {
+    Examples(Vec<Example>),
+    Text { heading: String, body: String },
+}
+
+/// An example invocation from the `# Examples` section
+#[derive(Clone, Debug)]
+pub struct Example {
+    /// The command line, starting with the name of the program
+    pub command: String,
+    pub explanation: String,
+}
+
+impl Example {
+    /// The args of the example after the name of the program
+    pub fn args(&self) -> Vec<String> {
+        split_command_line(&self.command).into_iter().skip(1).collect()
+    }
+}
+
+/// Splits a command line into words like a shell would, supporting quotes
+/// and backslash escapes
+fn split_command_line(line: &str) -> Vec<String> {
+    let mut words = vec![];
+    let mut word: Option<String> = None;
+    let mut quote = None;
+    let mut chars = line.chars();
+
+    while let Some(c) = chars.next() {
+        match (c, quote) {
+            ('\\', Some('\'')) => word.get_or_insert_with(String::new).push(c),
+            ('\\', _) => {
+                if let Some(escaped) = chars.next() {
+                    word.get_or_insert_with(String::new).push(escaped);
+                }
+            }
+            (c, Some(q)) if c == q => quote = None,
+            (c, Some(_)) => word.get_or_insert_with(String::new).push(c),
+            ('\'' | '"', None) => {
+                quote = Some(c);
+                word.get_or_insert_with(String::new);
+            }
+            (c, None) if c.is_whitespace() => words.extend(word.take()),
+            (c, None) => word.get_or_insert_with(String::new).push(c),
+        }
+    }
+
+    words.extend(word);
+    words
+}
+
+/// Parses the `# Examples` section, each example is a command line starting
+/// with `$ ` followed by the lines explaining it
+fn parse_examples(lines: &[String]) -> Vec<Example> {
+    let mut examples: Vec<Example> = vec![];
+
+    for line in lines {
+        let line = line.trim();
+        if let Some(command) = line.strip_prefix("$ ") {
+            examples.push(Example {
+                command: command.trim().to_string(),
+                explanation: String::new(),
+            });
+        } else if let Some(example) = examples.last_mut().filter(|_| !line.is_empty()) {
+            if !example.explanation.is_empty() {
+                example.explanation.push(' ');
+            }
+            example.explanation.push_str(line);
+        }
+    }
+
+    examples
+}
+
+pub fn parse_docs(attrs: &[Attribute]) -> Result<HashMap<String, DocData>, TokenStream> {
+    let (_, sections) = split_sections(doc_lines(attrs));
+    let lines = sections
+        .into_iter()
+        .find(|(heading, _)| heading == "Args")
+        .map(|(_, lines)| lines)
+        .unwrap_or_default();
+
+    let mut docs: Vec<DocData> = vec![];
+    for line in lines {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        // Indented lines add details to the previous arg, shown in the long help
+        match docs.last_mut() {
+            Some(last) if line.starts_with("  ") => {
+                if !last.details.is_empty() {
+                    last.details.push('\n');
+                }
+                last.details.push_str(line.trim());
+            }
+            _ => docs.push(line.parse()?),
+        }
+    }
+
+    Ok(docs.into_iter().map(|m| (m.long.clone(), m)).collect())
+}
+
+/// The sections of the docs shown in the long help, in the order they're written
+pub fn parse_sections(attrs: &[Attribute]) -> Vec<Section> {
+    let (_, sections) = split_sections(doc_lines(attrs));
+
+    sections
+        .into_iter()
+        .filter(|(heading, _)| heading != "Args")
+        .map(|(heading, lines)| {
+            if heading == "Examples" {
+                Section::Examples(parse_examples(&lines))
+            } else {
+                let body = lines.iter().map(|line| line.trim()).collect::<Vec<_>>().join("\n");
+                Section::Text {
+                    heading,
+                    body: body.trim().to_string(),
+                }
+            }
         })
-        .skip_while(|(name, value)| !(name == "doc" && value == "# Args"))
-        .skip(1)
-        .take_while(|(name, _)| name == "doc")
-        .map(|(_, value)| value.parse::<DocData>().map(|m| (m.long.clone(), m)))
         .collect()
 }
+
+/// Renders the examples as a markdown `# Examples` section, for the docs of
+/// the generated items
+pub fn examples_markdown(sections: &[Section]) -> String {
+    let mut markdown = String::new();
+    for section in sections {
+        if let Section::Examples(examples) = section {
+            markdown.push_str("\n# Examples\n");
+            for Example { command, explanation } in examples {
+                markdown.push_str(&format!("\n```text\n$ {command}\n```\n"));
+                if !explanation.is_empty() {
+                    markdown.push_str(&format!("{explanation}\n"));
+                }
+            }
+        }
+    }
+
+    markdown
+}
+
+/// The first paragraph of a description
+pub fn summary(description: &str) -> String {
+    description
+        .lines()
+        .skip_while(|line| line.trim().is_empty())
+        .take_while(|line| !line.trim().is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// The doc comment up until the first section
+pub fn parse_description(attrs: &[Attribute]) -> String {
+    let (description, _) = split_sections(doc_lines(attrs));
+    description.join("\n")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_split_command_line() {
+        assert_eq!(split_command_line("app pack  a.txt -l 9"), ["app", "pack", "a.txt", "-l", "9"]);
+        assert_eq!(
+            split_command_line(r#"app note "two words" 'a\b' a\ b """#),
+            ["app", "note", "two words", "a\\b", "a b", ""]
+        );
+    }
+}
@@ -1,70 +1,184 @@
-use crate::doc::{parse_docs, DocData};
+use crate::{
+    attr::{take_param_options, MethodOptions},
+    builtin::{builtin_checks, check_command_name},
+    doc::{parse_description, parse_docs, parse_sections, summary, DocData, Section},
+    naming::RenameRule,
+};
 use proc_macro2::TokenStream;
 use quote::quote;
-use syn::{Expr, ExprLit, ImplItemFn, Lit, Type};
+use syn::{spanned::Spanned, Attribute, Ident, ImplItemFn, Type};
+
+/// How a command method borrows the application instance
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ReceiverKind {
+    None,
+    Shared,
+    Mutable,
+}
+
+impl ReceiverKind {
+    /// The receiver to put in the signature of the generated entry points
+    pub fn to_param(self) -> TokenStream {
+        match self {
+            ReceiverKind::None => quote!(),
+            ReceiverKind::Shared => quote!(&self,),
+            ReceiverKind::Mutable => quote!(&mut self,),
+        }
+    }
+
+    /// The path prefix used to call into other generated functions
+    pub fn to_call(self) -> TokenStream {
+        match self {
+            ReceiverKind::None => quote!(Self::),
+            ReceiverKind::Shared | ReceiverKind::Mutable => quote!(self.),
+        }
+    }
+}
 
 pub struct Command {
     pub name: String,
     pub help: String,
+    /// The first paragraph of the help, used in the short help
+    pub summary: String,
+    /// The heading the command is listed under in the help
+    pub category: Option<String>,
     pub doc_data: Vec<(DocData, Box<Type>)>,
+    /// The `# Examples` and other sections of the docs after the description
+    pub sections: Vec<Section>,
+    pub receiver: ReceiverKind,
+    pub is_async: bool,
+    /// The `#[cfg]` attributes of the method, to be put on every generated item for it
+    pub cfgs: Vec<Attribute>,
+    /// The variant of the generated invocation enum for this command
+    pub variant: Ident,
+    /// The names of the parameters, used as the fields of the variant
+    pub fields: Vec<Ident>,
+    /// Expressions converting the parsed args into each field
+    pub conversions: Vec<TokenStream>,
+    /// Returns early if one of the built in flags was passed to the command
+    pub builtin_checks: TokenStream,
+    /// Calls the method with the fields of the variant in scope
     pub tokens: proc_macro2::TokenStream,
 }
 
-pub fn generate_command(func: &ImplItemFn) -> Result<Command, TokenStream> {
+pub fn generate_command(
+    func: &mut ImplItemFn,
+    options: MethodOptions,
+    rename_all: RenameRule,
+) -> Result<Command, TokenStream> {
     let doc_data = parse_docs(&func.attrs)?;
     let name = &func.sig.ident;
-    let name_string = name.to_string();
+    let name_string = options
+        .rename
+        .unwrap_or_else(|| rename_all.apply(&name.to_string()));
+    check_command_name(&name_string, name.span())?;
 
-    let (args, doc_data): (Vec<TokenStream>, Vec<(DocData, Box<Type>)>) = func
-        .sig
-        .inputs
-        .iter()
-        .filter_map(|item| match item {
-            syn::FnArg::Receiver(_) => None,
-            syn::FnArg::Typed(arg) => match arg.pat.as_ref() {
-                syn::Pat::Ident(ident) => Some((ident, arg.ty.to_owned())),
-                _ => None,
-            },
-        })
-        .map(|(item, ty)| {
-            let item_name = item.ident.to_string();
-            let doc_data = doc_data.get(item_name.as_str()).cloned().unwrap_or_else(|| DocData { long: item_name.to_string(), short: None, docs: "".into() });
-            let short = doc_data.short.as_ref();
+    let receiver = match func.sig.receiver() {
+        None => ReceiverKind::None,
+        Some(receiver) if receiver.reference.is_none() => {
+            let err = format!("{name_string} takes self by value, commands can only take &self or &mut self");
+            return Err(quote::quote_spanned!(receiver.span() => compile_error!(#err);));
+        }
+        Some(receiver) if receiver.mutability.is_some() => ReceiverKind::Mutable,
+        Some(_) => ReceiverKind::Shared,
+    };
 
-            if item_name == "input" {
-                (quote!(::argster::from_args::FromArgsItem::from_args_item(args.get("")).map_err(|x| (Some(#name_string), x.with_name("input")))?), (doc_data, ty))
-            } else if let Some(short) = short {
-                (quote!(::argster::from_args::FromArgsItem::from_args_item(args.get(#item_name).or_else(|| args.get(#short))).map_err(|x| (Some(#name_string), x.with_name(#item_name)))?), (doc_data, ty))
-            } else {
-                (quote!(::argster::from_args::FromArgsItem::from_args_item(args.get(#item_name)).map_err(|x| (Some(#name_string), x.with_name(#item_name)))?), (doc_data, ty))
+    let mut params = vec![];
+    for item in func.sig.inputs.iter_mut() {
+        if let syn::FnArg::Typed(arg) = item {
+            let options = take_param_options(&mut arg.attrs)?;
+            if let syn::Pat::Ident(ident) = arg.pat.as_ref() {
+                params.push((ident.ident.clone(), options, arg.ty.to_owned()));
             }
+        }
+    }
+
+    let (params, doc_data): (Vec<_>, Vec<_>) = params
+        .into_iter()
+        .map(|(field, options, ty)| {
+            let ident_name = field.to_string();
+            let item_name = if ident_name == "input" {
+                ident_name.clone()
+            } else {
+                options.rename.unwrap_or_else(|| rename_all.apply(&ident_name))
+            };
+            let mut doc_data = doc_data
+                .get(item_name.as_str())
+                .or_else(|| doc_data.get(ident_name.as_str()))
+                .cloned()
+                .unwrap_or_default();
+            doc_data.long = item_name.clone();
+            doc_data.section = options.section;
+            doc_data.default = options.default;
+            doc_data.env = options.env;
+
+            let item = if item_name == "input" {
+                quote!(args.get(""))
+            } else if let Some(short) = doc_data.short.as_ref() {
+                quote!(args.get(#item_name).or_else(|| args.get(#short)))
+            } else {
+                quote!(args.get(#item_name))
+            };
+            let conversion = if doc_data.default.is_none() && doc_data.env.is_none() {
+                quote!(::argster::from_args::FromArgsItem::from_args_item(#item))
+            } else {
+                let env = optional_str(&doc_data.env);
+                let default = optional_str(&doc_data.default);
+                quote!(::argster::from_args::from_args_or(#item, #env, #default))
+            };
+
+            ((field, quote!(#conversion.map_err(|x| (Some(#name_string), x.with_name(#item_name)))?)), (doc_data, ty))
         }).unzip();
+    let (fields, conversions): (Vec<_>, Vec<_>) = params.into_iter().unzip();
 
-    let help = func
+    let help = parse_description(&func.attrs);
+    let summary = summary(&help);
+    let sections = parse_sections(&func.attrs);
+
+    let call = receiver.to_call();
+    let is_async = func.sig.asyncness.is_some();
+    let tokens = if is_async {
+        quote!(#call #name(#(#fields),*).await)
+    } else {
+        quote!(#call #name(#(#fields),*))
+    };
+    let variant = Ident::new(&RenameRule::PascalCase.apply(&name.to_string()), name.span());
+    let cfgs = func
         .attrs
         .iter()
-        .filter_map(|item| {
-            let item = item.meta.require_name_value().ok()?;
-            if !item.path.is_ident("doc") {
-                return None;
-            }
+        .filter(|attr| attr.path().is_ident("cfg"))
+        .cloned()
+        .collect();
 
-            match item.value {
-                Expr::Lit(ExprLit {
-                    lit: Lit::Str(ref s),
-                    ..
-                }) => Some(s.value()),
-                _ => None,
-            }
-        })
-        .take_while(|item| item != " # Args")
-        .collect::<Vec<_>>()
-        .join("\n");
+    let params = doc_data
+        .iter()
+        .zip(&fields)
+        .map(|((doc, _), field)| (doc.clone(), field.span()))
+        .collect::<Vec<_>>();
+    let builtin_checks = builtin_checks(&name_string, &params)?;
 
     Ok(Command {
         name: name_string,
         help,
+        summary,
+        category: options.category,
         doc_data,
-        tokens: quote!(Self::#name(#(#args),*)),
+        sections,
+        receiver,
+        is_async,
+        cfgs,
+        variant,
+        fields,
+        conversions,
+        builtin_checks,
+        tokens,
     })
 }
+
+/// Quotes an optional string as an `Option<&str>` expression
+pub fn optional_str(value: &Option<String>) -> TokenStream {
+    match value {
+        Some(value) => quote!(Some(#value)),
+        None => quote!(None),
+    }
+}
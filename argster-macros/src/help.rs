@@ -1,104 +1,157 @@
-use crate::{doc::DocData, function::Command};
+use crate::{
+    attr::ImplOptions,
+    doc::{DocData, Example, Section},
+    function::{optional_str, Command},
+};
 use proc_macro::TokenStream;
 use quote::quote;
 use syn::Type;
 
-pub fn generate_help(commands: &[Command]) -> TokenStream {
-    let help_table = commands.iter().map(|Command { name, help, .. }| {
-        quote!(Row::new(vec![
-            Cell::new(&#name.to_string()).style_spec("bFG"),
-            Cell::new(&#help.to_string()),
-        ]))
-    });
+pub fn generate_help(commands: &[Command], options: &ImplOptions, description: &str) -> TokenStream {
+    let app_name = options.app_name();
+    let version = options.app_version();
+    let about = options.app_about();
+    let renderer = options.help_renderer();
+    let theme = options.help_theme();
+    let error_help = options.error_help();
+    let description = description
+        .lines()
+        .map(str::trim)
+        .collect::<Vec<_>>()
+        .join("\n");
+    let description = description.trim();
 
-    let commands_help_table = commands.iter().map(|command| {
-        let help_table = generate_command_help(&command.doc_data);
+    let command_help = commands.iter().map(|command| {
         let name = &command.name;
+        let category = optional_str(&command.category);
+        let summary = &command.summary;
         let help = &command.help;
-        quote!(Some(s) if s == #name => {
-            _ = stderr.attr(Attr::Bold);
-            _ = stderr.fg(color::BRIGHT_GREEN);
-            _ = write!(stderr, #name);
-            _ = stderr.reset();
-            _ = writeln!(stderr, #help);
-
-            table = Table::init(#help_table);
-        })
+        let cfgs = &command.cfgs;
+        let options = generate_options(&command.doc_data);
+        let sections = generate_sections(&command.sections);
+        quote!(
+            #(#cfgs)*
+            commands.push(::argster::help::CommandHelp {
+                name: #name,
+                category: #category,
+                summary: #summary,
+                description: #help,
+                options: vec![#(#options),*],
+                sections: vec![#(#sections),*],
+            });
+        )
     });
 
     quote!(
-        fn __argster_help(command: Option<String>, error: Option<::argster::from_args::Error>) {
-            use ::argster::{
-                prettytable::{
-                    format::{LinePosition, LineSeparator, TableFormat, consts::FORMAT_CLEAN},
-                    Cell, Row, Table,
+        fn __argster_help(out: &mut ::argster::term::Writer<'_>, topic: &::argster::help::Topic, error: Option<&::argster::from_args::Error>, long: bool) {
+            let mut commands = Vec::new();
+            #(#command_help)*
+
+            let page = topic.page(&commands);
+            let help = ::argster::help::Help {
+                app: ::argster::help::AppHelp {
+                    name: #app_name,
+                    version: #version,
+                    about: #about,
+                    description: #description,
                 },
-                term::*,
+                error,
+                long,
+                page,
+                theme: #theme,
             };
 
-            let mut stderr = stderr().unwrap();
-            _ = stderr.attr(Attr::Bold);
-            _ = write!(stderr, "{}", env!("CARGO_PKG_NAME"));
-            _ = stderr.reset();
-            _ = stderr.fg(color::BRIGHT_BLACK);
-            _ = writeln!(stderr, " {}", env!("CARGO_PKG_VERSION"));
-            _ = stderr.reset();
-
-            if env!("CARGO_PKG_DESCRIPTION").len() > 0 {
-                _ = writeln!(stderr, "{}", env!("CARGO_PKG_DESCRIPTION"));
-            }
-
-            if let Some(error) = error {
-                _ = stderr.attr(Attr::Bold);
-                _ = stderr.fg(color::BRIGHT_RED);
-                _ = write!(stderr, "Error");
-                _ = stderr.reset();
-                _ = writeln!(stderr, ": {}", error);
+            // Running without a command isn't much of a mistake, so it still
+            // lists the commands
+            match error {
+                Some(::argster::from_args::Error::NoCommand) | None => ::argster::help::HelpRenderer::render(&(#renderer), out, &help),
+                Some(error) => match #error_help {
+                    ::argster::help::ErrorHelp::Compact => ::argster::help::HelpRenderer::render_error(&(#renderer), out, &help, error),
+                    ::argster::help::ErrorHelp::Full => ::argster::help::HelpRenderer::render(&(#renderer), out, &help),
+                },
             }
+        }
+    )
+    .into()
+}
 
-            _ = writeln!(stderr, "");
+pub fn generate_version(options: &ImplOptions) -> TokenStream {
+    let app_name = options.app_name();
+    let version = options.app_version();
+    let details = [("commit", &options.commit), ("build-date", &options.build_date)]
+        .into_iter()
+        .filter_map(|(label, value)| {
+            let value = value.as_ref()?;
+            Some(quote!(_ = writeln!(out, "{}: {}", #label, #value);))
+        });
 
-            let mut table;
-            let content = match command.as_ref() {
-                None => {
-                    _ = stderr.attr(Attr::Bold);
-                    _ = writeln!(stderr, "Commands:");
-                    _ = stderr.reset();
-                    table = Table::init(vec![#(#help_table),*]);
-                },
-                #(#commands_help_table),*,
-                _ => todo!(),
-            };
+    quote!(
+        fn __argster_version(out: &mut ::argster::term::Writer<'_>, verbose: bool) {
+            use ::std::io::Write;
 
-            table.set_format(*FORMAT_CLEAN);
-            _ = table.print_term(stderr.as_mut());
+            _ = writeln!(out, "{} {}", #app_name, #version);
+            if verbose {
+                #(#details)*
+            }
         }
     )
     .into()
 }
 
-pub fn generate_command_help(doc_data: &[(DocData, Box<Type>)]) -> proc_macro2::TokenStream {
-    let help = doc_data
+/// Generates the description of each option of a command
+fn generate_options(doc_data: &[(DocData, Box<Type>)]) -> Vec<proc_macro2::TokenStream> {
+    doc_data
         .iter()
-        .map(|(DocData { docs, short, long }, typ)| {
-            let long = if long == "input" {
-                "input".to_string()
+        .map(|(DocData { long, short, docs, details, section, default, env }, typ)| {
+            let positional = long == "input";
+            let short = optional_str(short);
+            let section = optional_str(section);
+            // A default or environment variable can stand in for the option
+            let arity = if default.is_some() || env.is_some() {
+                quote!(<#typ as ::argster::from_args::FromArgsItem>::ARITY.optional())
             } else {
-                format!("--{}", long) 
-            };
-            let short = match short.as_ref() {
-                Some(f) => format!("--{f}"),
-                None => "".to_string(),
+                quote!(<#typ as ::argster::from_args::FromArgsItem>::ARITY)
             };
-            quote!(Row::new(vec![
-                Cell::new(&#long.to_string()).style_spec("bFG"),
-                Cell::new(&#short.to_string()).style_spec("bFG"),
-                Cell::new(&<#typ as ::argster::from_args::FromArgsItem>::TYPE_NAME.to_string()).style_spec("FD"),
-                Cell::new(&<#typ as ::argster::from_args::FromArgsItem>::TYPE_DESC.to_string()).style_spec("FD"),
-                Cell::new(&&<#typ as ::argster::from_args::FromArgsItem>::TYPE_EXTRA.to_string().to_string()).style_spec("FD"),
-                Cell::new(&#docs.to_string()),
-            ]))
-        });
+            let default = optional_str(default);
+            let env = optional_str(env);
 
-    quote!(vec![#(#help),*])
+            quote!(::argster::help::OptionHelp {
+                name: #long,
+                short: #short,
+                positional: #positional,
+                type_name: <#typ as ::argster::from_args::FromArgsItem>::TYPE_NAME,
+                type_desc: <#typ as ::argster::from_args::FromArgsItem>::TYPE_DESC,
+                type_extra: <#typ as ::argster::from_args::FromArgsItem>::TYPE_EXTRA,
+                arity: #arity,
+                docs: #docs,
+                details: #details,
+                default: #default,
+                env: #env,
+                possible_values: <#typ as ::argster::from_args::FromArgsItem>::POSSIBLE_VALUES,
+                section: #section,
+            })
+        })
+        .collect()
+}
+
+/// Generates the sections of a command's docs shown below its options
+fn generate_sections(sections: &[Section]) -> Vec<proc_macro2::TokenStream> {
+    sections
+        .iter()
+        .map(|section| match section {
+            Section::Examples(examples) => {
+                let examples = examples.iter().map(|Example { command, explanation }| {
+                    quote!(::argster::help::Example {
+                        command: #command,
+                        explanation: #explanation,
+                    })
+                });
+                quote!(::argster::help::DocSection::Examples(vec![#(#examples),*]))
+            }
+            Section::Text { heading, body } => quote!(::argster::help::DocSection::Text {
+                heading: #heading,
+                body: #body,
+            }),
+        })
+        .collect()
 }
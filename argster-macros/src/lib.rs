@@ -1,25 +1,41 @@
-use help::generate_help;
+use help::{generate_help, generate_version};
 use proc_macro::TokenStream;
-use quote::{quote, ToTokens};
-use syn::{parse_macro_input, ImplItem, ImplItemFn, ItemImpl};
+use quote::{format_ident, quote, quote_spanned, ToTokens};
+use syn::{parse_macro_input, spanned::Spanned, ImplItem, ImplItemFn, ItemImpl, Type};
 
-use crate::function::{generate_command, Command};
+use crate::{
+    attr::{take_method_options, ImplOptions},
+    builtin::{command_flag_names, global_flags},
+    doc::{examples_markdown, parse_description, DocData, Section},
+    function::{generate_command, Command, ReceiverKind},
+};
 
+mod attr;
+mod builtin;
 mod doc;
 mod function;
 mod help;
+mod naming;
 
 #[proc_macro_attribute]
-pub fn command(_attr: TokenStream, item: TokenStream) -> TokenStream {
+pub fn command(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let mut options = ImplOptions::default();
+    let options_parser = syn::meta::parser(|meta| options.parse(meta));
+    parse_macro_input!(attr with options_parser);
+
     let mut tree = parse_macro_input!(item as ItemImpl);
     let commands = tree
         .items
-        .iter()
+        .iter_mut()
         .filter_map(|ex| match ex {
             ImplItem::Fn(func) => Some(func),
             _ => None,
         })
-        .map(generate_command)
+        .filter_map(|func| match take_method_options(&mut func.attrs) {
+            Ok(options) if options.skip => None,
+            Ok(method_options) => Some(generate_command(func, method_options, options.rename_all)),
+            Err(err) => Some(Err(err)),
+        })
         .collect::<Result<Vec<Command>, proc_macro2::TokenStream>>();
 
     if let Err(err) = commands {
@@ -28,56 +44,369 @@ pub fn command(_attr: TokenStream, item: TokenStream) -> TokenStream {
 
     let commands = commands.unwrap();
 
-    let main_generator = commands.iter().map(|Command { name, tokens, .. }| {
+    let receiver_kind = commands
+        .iter()
+        .map(|command| command.receiver)
+        .max()
+        .unwrap_or(ReceiverKind::None);
+    let receiver = receiver_kind.to_param();
+    let call = receiver_kind.to_call();
+
+    // If any command is async the dispatcher has to be async as well, sync
+    // commands are simply called from inside it
+    let is_async = commands.iter().any(|command| command.is_async);
+    let (asyncness, await_dispatch, dispatch, run_with) = if is_async {
+        (
+            quote!(async),
+            quote!(.await),
+            format_ident!("dispatch_async"),
+            format_ident!("run_with_async"),
+        )
+    } else {
+        (
+            quote!(),
+            quote!(),
+            format_ident!("dispatch"),
+            format_ident!("run_with"),
+        )
+    };
+
+    let enum_name = match tree.self_ty.as_ref() {
+        Type::Path(path) => match path.path.segments.last() {
+            Some(segment) => format_ident!("{}Command", segment.ident),
+            None => return quote!(compile_error!("Expected a type name");).into(),
+        },
+        _ => {
+            return quote_spanned!(tree.self_ty.span() => compile_error!("#[command] can only be used on an impl for a named type");).into()
+        }
+    };
+
+    let variants = commands.iter().map(|Command { variant, help, fields, doc_data, sections, cfgs, .. }| {
+        let help = format!("{help}\n{}", examples_markdown(sections));
+        let types = doc_data.iter().map(|(_, ty)| ty);
+        let docs = doc_data.iter().map(|(DocData { docs, .. }, _)| docs);
         quote!(
+            #(#cfgs)*
+            #[doc = #help]
+            #variant {
+                #(
+                    #[doc = #docs]
+                    #fields: #types
+                ),*
+            }
+        )
+    });
+
+    let parse_generator = commands.iter().map(|Command { name, variant, fields, conversions, builtin_checks, cfgs, .. }| {
+        quote!(
+            #(#cfgs)*
             #name => {
-                #tokens
+                #builtin_checks
+                Ok(#enum_name::#variant {
+                    #(#fields: #conversions),*
+                })
             }
         )
     });
 
-    let argster_main = quote!(
-        fn __argster_main() -> Result<(), (Option<&'static str>, ::argster::from_args::Error)> {
-            let mut iter = ::std::env::args().skip(1);
+    let command_names = commands.iter().map(|Command { name, cfgs, .. }| quote!(#(#cfgs)* #name));
+
+    let dispatch_generator = commands.iter().map(|Command { variant, fields, tokens, cfgs, .. }| {
+        quote!(
+            #(#cfgs)*
+            #enum_name::#variant { #(#fields),* } => {
+                #tokens
+            }
+        )
+    }).collect::<Vec<_>>();
+
+    let vis = options.visibility();
+    let derives = &options.derives;
+    let enum_doc = format!("A parsed invocation of one of the commands of [`{}`]", tree.self_ty.to_token_stream());
+    let invocation_enum = quote!(
+        #[doc = #enum_doc]
+        #[derive(#(#derives),*)]
+        #vis enum #enum_name {
+            #(#variants),*
+        }
+    );
+
+    let try_parse_from = quote!(
+        /// Parses the args into an invocation without running it
+        #vis fn try_parse_from(args: impl IntoIterator<Item = impl Into<String>>) -> Result<#enum_name, ::argster::from_args::Error> {
+            Self::__argster_parse(args).map_err(|(_, ex)| ex)
+        }
+    )
+    .into();
+
+    let argster_parse = quote!(
+        fn __argster_parse(args: impl IntoIterator<Item = impl Into<String>>) -> Result<#enum_name, (Option<&'static str>, ::argster::from_args::Error)> {
+            let (_, args) = Self::__argster_global_flags(args)?;
+            let mut iter = args.into_iter();
             let command = iter.next().ok_or_else(|| (None, ::argster::from_args::Error::NoCommand))?;
             let args = ::argster::parse_args(iter);
 
-            if (args.get("--help").is_some() || args.get("-h").is_some()) {
-                Self::__argster_help(Some(command), None);
-                return Ok(());
+            match command.as_str() {
+                #(#parse_generator,)*
+                "help" | "--help" | "-h" => {
+                    let long = command != "-h";
+                    let target: Option<String> = ::argster::from_args::FromArgsItem::from_args_item(args.get("")).map_err(|x| (Some("help"), x.with_name("input")))?;
+                    let search: Option<String> = ::argster::from_args::FromArgsItem::from_args_item(args.get("search")).map_err(|x| (Some("help"), x.with_name("search")))?;
+                    let topic = match (target, search) {
+                        (_, Some(term)) => ::argster::help::Topic::Search(term),
+                        _ if args.contains_key("all") => ::argster::help::Topic::All,
+                        (Some(target), _) if !Self::__argster_command_names().contains(&target.as_str()) => {
+                            return Err((None, ::argster::from_args::Error::UnknownCommand(target)));
+                        }
+                        (Some(target), _) => ::argster::help::Topic::Command(target),
+                        (None, _) => ::argster::help::Topic::Commands,
+                    };
+                    // The full help of every command is the point of `--all`
+                    let long = long || topic == ::argster::help::Topic::All;
+                    Err((None, ::argster::from_args::Error::Help { topic, long }))
+                }
+                "version" | "--version" | "-V" => Err((None, ::argster::from_args::Error::Version { verbose: args.get("verbose").is_some() })),
+                _ => Err((None, ::argster::from_args::Error::UnknownCommand(command))),
             }
+        }
+    )
+    .into();
 
-            match command.as_str() {
-                #(#main_generator),*,
-                "help" | "--help" | "-h" => Self::__argster_help(::argster::from_args::FromArgsItem::from_args_item(args.get("")).map_err(|x| (Some("help"), x.with_name("input")))?, None),
-                _ => panic!("Unknown command {}", command),
-            };
+    let example_checks = commands.iter().map(|Command { name, doc_data, sections, cfgs, .. }| {
+        let examples = sections
+            .iter()
+            .filter_map(|section| match section {
+                Section::Examples(examples) => Some(examples),
+                _ => None,
+            })
+            .flatten()
+            .map(|example| {
+                let command = &example.command;
+                let args = example.args();
+                quote!((#command, &[#(#args),*]))
+            })
+            .collect::<Vec<_>>();
+        if examples.is_empty() {
+            return quote!();
+        }
+
+        // Parsing ignores options a command doesn't take, so a renamed option
+        // is only caught by comparing against the names the command knows
+        let known = doc_data
+            .iter()
+            .flat_map(|(DocData { long, short, .. }, _)| {
+                let long = if long == "input" { "" } else { long.as_str() };
+                std::iter::once(long).chain(short.as_deref())
+            })
+            .chain(command_flag_names());
+
+        quote!(
+            #(#cfgs)*
+            {
+                let known: &[&str] = &[#(#known),*];
+                let examples: &[(&'static str, &[&str])] = &[#(#examples),*];
+                for (example, args) in examples {
+                    match Self::__argster_parse(args.iter().copied()) {
+                        Ok(_) | Err((_, ::argster::from_args::Error::Help { .. } | ::argster::from_args::Error::Version { .. })) => {}
+                        Err((_, ex)) => return Err((example, ex)),
+                    }
+
+                    let (_, args) = Self::__argster_global_flags(args.iter().copied()).map_err(|(_, ex)| (*example, ex))?;
+                    if args.first().map(String::as_str) == Some(#name) {
+                        let args = ::argster::parse_args(args.into_iter().skip(1));
+                        if let Some(option) = args.into_keys().find(|key| !known.contains(&key.as_str())) {
+                            return Err((example, ::argster::from_args::Error::UnknownOption(option)));
+                        }
+                    }
+                }
+            }
+        )
+    });
+
+    let verify_examples = quote!(
+        /// Parses the `# Examples` in the docs of every command without running
+        /// them, returning the first example that doesn't match its command
+        #vis fn verify_examples() -> Result<(), (&'static str, ::argster::from_args::Error)> {
+            #(#example_checks)*
             Ok(())
         }
     )
     .into();
 
-    let main = quote!(
-        fn main() {
-            match Self::__argster_main() {
-                Ok(_) => (),
-                Err((name, ex)) => Self::__argster_help(name.map(|f| f.to_string()), Some(ex)),
-            }
+    let argster_main = quote!(
+        #asyncness fn __argster_main(#receiver args: impl IntoIterator<Item = impl Into<String>>) -> Result<(), (Option<&'static str>, ::argster::from_args::Error)> {
+            let command = Self::__argster_parse(args)?;
+            #call #dispatch(command) #await_dispatch;
+            Ok(())
         }
     )
     .into();
 
-    let help = generate_help(&commands);
+    let help = generate_help(&commands, &options, &parse_description(&tree.attrs));
+    let version = generate_version(&options);
 
-    // println!("{}", help);
     tree.items
-        .push(ImplItem::Fn(parse_macro_input!(argster_main as ImplItemFn)));
+        .push(ImplItem::Fn(parse_macro_input!(try_parse_from as ImplItemFn)));
 
     tree.items
-        .push(ImplItem::Fn(parse_macro_input!(main as ImplItemFn)));
+        .push(ImplItem::Fn(parse_macro_input!(argster_parse as ImplItemFn)));
+
+    let argster_global_flags = global_flags().into();
+    tree.items
+        .push(ImplItem::Fn(parse_macro_input!(argster_global_flags as ImplItemFn)));
+
+    tree.items
+        .push(ImplItem::Fn(parse_macro_input!(verify_examples as ImplItemFn)));
+
+    let argster_command_names = quote!(
+        fn __argster_command_names() -> &'static [&'static str] {
+            &[#(#command_names),*]
+        }
+    )
+    .into();
+
+    tree.items
+        .push(ImplItem::Fn(parse_macro_input!(argster_command_names as ImplItemFn)));
+
+    tree.items
+        .push(ImplItem::Fn(parse_macro_input!(argster_main as ImplItemFn)));
+
+    let entry_points = [
+        (
+            "dispatch",
+            "Runs a parsed invocation",
+            quote!(command: #enum_name),
+            quote!(),
+            quote!(command),
+            quote!(
+                match command {
+                    #(#dispatch_generator),*
+                }
+            ),
+        ),
+        (
+            "run_with",
+            "Runs the command line given by `args`, writing any help or errors to `out`",
+            quote!(args: impl IntoIterator<Item = impl Into<String>>, out: &mut ::argster::term::Writer<'_>),
+            quote!(-> Result<(), ::argster::from_args::Error>),
+            quote!(args, out),
+            quote!(
+                let result = match Self::__argster_global_flags(args) {
+                    Ok((flags, args)) => {
+                        out.apply_flags(&flags);
+                        let _color = ::argster::term::ColorChoiceScope::enter(flags.color.unwrap_or_default());
+                        #call __argster_main(args) #await_dispatch
+                    }
+                    Err(ex) => Err(ex),
+                };
+                match result {
+                    Ok(_) => Ok(()),
+                    Err((_, ::argster::from_args::Error::Help { topic, long })) => {
+                        out.requested_output();
+                        out.paged(|out| Self::__argster_help(out, &topic, None, long));
+                        Ok(())
+                    }
+                    Err((_, ::argster::from_args::Error::Version { verbose })) => {
+                        out.requested_output();
+                        Self::__argster_version(out, verbose);
+                        Ok(())
+                    }
+                    Err((name, ex)) => {
+                        let topic = name.map_or(::argster::help::Topic::Commands, |name| ::argster::help::Topic::Command(name.to_string()));
+                        Self::__argster_help(out, &topic, Some(&ex), false);
+                        Err(ex)
+                    }
+                }
+            ),
+        ),
+        (
+            "run_from",
+            "Runs the command line given by `args`, capturing any help or errors in the outcome",
+            quote!(args: impl IntoIterator<Item = impl Into<String>>),
+            quote!(-> ::argster::Outcome),
+            quote!(args),
+            quote!(
+                let mut output = Vec::new();
+                let mut out = ::argster::term::Writer::plain(&mut output);
+                let result = #call #run_with(args, &mut out) #await_dispatch;
+                let color = out.color();
+                drop(out);
+                ::argster::Outcome {
+                    color,
+                    ..::argster::Outcome::new(result, output)
+                }
+            ),
+        ),
+        (
+            "run",
+            "Runs the command line the program was started with, exiting with `Outcome::USAGE_ERROR` if it couldn't be parsed",
+            quote!(),
+            quote!(),
+            quote!(),
+            quote!(
+                let result = #call #run_with(::std::env::args().skip(1), &mut ::argster::term::Writer::standard()) #await_dispatch;
+                if result.is_err() {
+                    ::std::process::exit(::argster::Outcome::USAGE_ERROR);
+                }
+            ),
+        ),
+    ];
+
+    for (name, doc, params, output, forward, body) in entry_points {
+        let name = format_ident!("{name}");
+        let entry_point = if is_async {
+            let async_name = format_ident!("{name}_async");
+            let async_entry_point = quote!(
+                #[doc = #doc]
+                #vis async fn #async_name(#receiver #params) #output {
+                    #body
+                }
+            )
+            .into();
+
+            tree.items
+                .push(ImplItem::Fn(parse_macro_input!(async_entry_point as ImplItemFn)));
+
+            quote!(
+                #[doc = #doc]
+                #vis fn #name(#receiver #params) #output {
+                    ::argster::rt::block_on(#call #async_name(#forward))
+                }
+            )
+        } else {
+            quote!(
+                #[doc = #doc]
+                #vis fn #name(#receiver #params) #output {
+                    #body
+                }
+            )
+        }
+        .into();
+
+        tree.items
+            .push(ImplItem::Fn(parse_macro_input!(entry_point as ImplItemFn)));
+    }
+
+    // A static main can only be provided when no command needs an instance
+    if receiver_kind == ReceiverKind::None {
+        let main = quote!(
+            #vis fn main() {
+                Self::run();
+            }
+        )
+        .into();
+
+        tree.items
+            .push(ImplItem::Fn(parse_macro_input!(main as ImplItemFn)));
+    }
 
     tree.items
         .push(ImplItem::Fn(parse_macro_input!(help as ImplItemFn)));
 
-    tree.to_token_stream().into()
+    tree.items
+        .push(ImplItem::Fn(parse_macro_input!(version as ImplItemFn)));
+
+    let mut tokens = tree.to_token_stream();
+    tokens.extend(invocation_enum);
+    tokens.into()
 }
@@ -0,0 +1,119 @@
+use std::str::FromStr;
+
+/// How Rust identifiers are turned into command and option names
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RenameRule {
+    /// Keeps the identifier as written
+    Verbatim,
+    Lowercase,
+    Uppercase,
+    PascalCase,
+    CamelCase,
+    SnakeCase,
+    ScreamingSnakeCase,
+    #[default]
+    KebabCase,
+    ScreamingKebabCase,
+}
+
+impl RenameRule {
+    pub const NAMES: &'static [&'static str] = &[
+        "verbatim",
+        "lowercase",
+        "UPPERCASE",
+        "PascalCase",
+        "camelCase",
+        "snake_case",
+        "SCREAMING_SNAKE_CASE",
+        "kebab-case",
+        "SCREAMING-KEBAB-CASE",
+    ];
+
+    /// Applies the rule to a snake_case identifier
+    pub fn apply(self, name: &str) -> String {
+        let name = name.strip_prefix("r#").unwrap_or(name);
+        let words = name.split('_').filter(|word| !word.is_empty());
+
+        match self {
+            RenameRule::Verbatim => name.to_string(),
+            RenameRule::Lowercase => words.collect::<String>().to_lowercase(),
+            RenameRule::Uppercase => words.collect::<String>().to_uppercase(),
+            RenameRule::PascalCase => words.map(capitalize).collect(),
+            RenameRule::CamelCase => {
+                let pascal = RenameRule::PascalCase.apply(name);
+                let mut chars = pascal.chars();
+                match chars.next() {
+                    Some(first) => first.to_lowercase().chain(chars).collect(),
+                    None => String::new(),
+                }
+            }
+            RenameRule::SnakeCase => words.collect::<Vec<_>>().join("_").to_lowercase(),
+            RenameRule::ScreamingSnakeCase => words.collect::<Vec<_>>().join("_").to_uppercase(),
+            RenameRule::KebabCase => words.collect::<Vec<_>>().join("-").to_lowercase(),
+            RenameRule::ScreamingKebabCase => words.collect::<Vec<_>>().join("-").to_uppercase(),
+        }
+    }
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+impl FromStr for RenameRule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let rule = match s {
+            "verbatim" => RenameRule::Verbatim,
+            "lowercase" => RenameRule::Lowercase,
+            "UPPERCASE" => RenameRule::Uppercase,
+            "PascalCase" => RenameRule::PascalCase,
+            "camelCase" => RenameRule::CamelCase,
+            "snake_case" => RenameRule::SnakeCase,
+            "SCREAMING_SNAKE_CASE" => RenameRule::ScreamingSnakeCase,
+            "kebab-case" => RenameRule::KebabCase,
+            "SCREAMING-KEBAB-CASE" => RenameRule::ScreamingKebabCase,
+            _ => {
+                return Err(format!(
+                    "unknown rename rule '{s}', expected one of {}",
+                    RenameRule::NAMES.join(", ")
+                ))
+            }
+        };
+
+        Ok(rule)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_rename_rules() {
+        let cases = [
+            ("verbatim", "dry_run_all"),
+            ("lowercase", "dryrunall"),
+            ("UPPERCASE", "DRYRUNALL"),
+            ("PascalCase", "DryRunAll"),
+            ("camelCase", "dryRunAll"),
+            ("snake_case", "dry_run_all"),
+            ("SCREAMING_SNAKE_CASE", "DRY_RUN_ALL"),
+            ("kebab-case", "dry-run-all"),
+            ("SCREAMING-KEBAB-CASE", "DRY-RUN-ALL"),
+        ];
+
+        for (rule, expected) in cases {
+            assert_eq!(rule.parse::<RenameRule>().unwrap().apply("dry_run_all"), expected);
+        }
+    }
+
+    #[test]
+    fn test_unknown_rule() {
+        assert!("Title Case".parse::<RenameRule>().is_err());
+    }
+}
@@ -1,4 +1,4 @@
-use crate::ArgsItem;
+use crate::{help::Topic, ArgsItem};
 use thiserror::Error;
 
 mod impls;
@@ -17,6 +17,22 @@ pub enum Error {
 
     #[error("Please enter a commond")]
     NoCommand,
+
+    #[error("Unknown command {0}")]
+    UnknownCommand(String),
+
+    /// An option the command doesn't take, only reported when verifying examples
+    #[error("Unknown option {0}")]
+    UnknownOption(String),
+
+    /// Help was requested instead of a command, `long` is set for `--help`
+    /// and unset for `-h`
+    #[error("Help requested")]
+    Help { topic: Topic, long: bool },
+
+    /// The version was requested instead of a command
+    #[error("Version requested")]
+    Version { verbose: bool },
 }
 
 impl Error {
@@ -33,6 +49,49 @@ impl Error {
                 found,
             },
             Error::NoCommand => Error::NoCommand,
+            Error::UnknownCommand(command) => Error::UnknownCommand(command),
+            Error::UnknownOption(option) => Error::UnknownOption(option),
+            Error::Help { topic, long } => Error::Help { topic, long },
+            Error::Version { verbose } => Error::Version { verbose },
+        }
+    }
+}
+
+/// How an argument is given on the command line, used when describing it in help
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Arity {
+    /// The argument must be given
+    pub required: bool,
+    /// The argument is followed by a value, rather than being a flag
+    pub takes_value: bool,
+    /// The argument can be given more than once
+    pub repeated: bool,
+}
+
+impl Arity {
+    pub const REQUIRED: Arity = Arity {
+        required: true,
+        takes_value: true,
+        repeated: false,
+    };
+
+    pub const FLAG: Arity = Arity {
+        required: false,
+        takes_value: false,
+        repeated: false,
+    };
+
+    pub const fn optional(self) -> Arity {
+        Arity {
+            required: false,
+            ..self
+        }
+    }
+
+    pub const fn repeated(self) -> Arity {
+        Arity {
+            repeated: true,
+            ..self
         }
     }
 }
@@ -41,8 +100,30 @@ pub trait FromArgsItem {
     const TYPE_DESC: &'static str;
     const TYPE_EXTRA: &'static str = "required";
     const TYPE_NAME: &'static str;
+    const ARITY: Arity = Arity::REQUIRED;
+    /// The values the argument accepts, shown in help, empty if any value of
+    /// the type is accepted
+    const POSSIBLE_VALUES: &'static [&'static str] = &[];
 
     fn from_args_item(item: Option<&ArgsItem>) -> Result<Self, Error>
     where
         Self: Sized;
 }
+
+/// Converts an argument, falling back to the environment variable `env` and
+/// then to `default` when it wasn't given on the command line
+pub fn from_args_or<T: FromArgsItem>(
+    item: Option<&ArgsItem>,
+    env: Option<&str>,
+    default: Option<&str>,
+) -> Result<T, Error> {
+    if item.is_some() {
+        return T::from_args_item(item);
+    }
+
+    let fallback = env
+        .and_then(|env| std::env::var(env).ok())
+        .or_else(|| default.map(String::from))
+        .map(ArgsItem::String);
+    T::from_args_item(fallback.as_ref())
+}
@@ -64,6 +64,7 @@ impl FromArgsItem for bool {
     const TYPE_NAME: &'static str = "bool";
     const TYPE_DESC: &'static str = "<true|false>";
     const TYPE_EXTRA: &'static str = "flag";
+    const ARITY: Arity = Arity::FLAG;
 
     fn from_args_item(item: Option<&ArgsItem>) -> Result<Self, Error> {
         match item {
@@ -131,6 +132,8 @@ where
     const TYPE_NAME: &'static str = T::TYPE_NAME;
     const TYPE_DESC: &'static str = T::TYPE_DESC;
     const TYPE_EXTRA: &'static str = "optinal";
+    const ARITY: Arity = T::ARITY.optional();
+    const POSSIBLE_VALUES: &'static [&'static str] = T::POSSIBLE_VALUES;
 
     fn from_args_item(item: Option<&ArgsItem>) -> Result<Self, Error>
     where
@@ -147,6 +150,8 @@ where
     const TYPE_NAME: &'static str = T::TYPE_NAME;
     const TYPE_DESC: &'static str = T::TYPE_DESC;
     const TYPE_EXTRA: &'static str = "list";
+    const ARITY: Arity = T::ARITY.repeated();
+    const POSSIBLE_VALUES: &'static [&'static str] = T::POSSIBLE_VALUES;
 
     fn from_args_item(item: Option<&ArgsItem>) -> Result<Self, Error> {
         match item {
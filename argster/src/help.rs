@@ -0,0 +1,825 @@
+//! Rendering of the generated help
+//!
+//! The macro describes the app and its commands with a [`Help`], which is
+//! laid out by a [`HelpRenderer`]. The default renderer draws tables with
+//! `prettytable`, without the `prettytable` feature the dependency free
+//! [`PlainRenderer`] is used instead. Doc comments are rendered as
+//! markdown, see [`print_markdown`].
+
+use crate::{
+    from_args::{Arity, Error},
+    style::{Style, Theme},
+    term::Writer,
+};
+use std::io::Write;
+use unicode_width::UnicodeWidthStr;
+
+mod markdown;
+mod plain;
+#[cfg(feature = "prettytable")]
+mod table;
+
+pub use markdown::{layout, print_markdown, strip_markdown, Span};
+pub use plain::PlainRenderer;
+#[cfg(feature = "prettytable")]
+pub use table::TableRenderer;
+
+/// The renderer used unless the impl sets one with `#[command(renderer = ..)]`
+#[cfg(feature = "prettytable")]
+pub type DefaultRenderer = TableRenderer;
+/// The renderer used unless the impl sets one with `#[command(renderer = ..)]`
+#[cfg(not(feature = "prettytable"))]
+pub type DefaultRenderer = PlainRenderer;
+
+/// Indent of the lines a description is wrapped onto
+const HANGING_INDENT: &str = "  ";
+
+/// The narrowest a wrapped column gets, even if the table doesn't fit
+const MIN_COLUMN_WIDTH: usize = 20;
+
+/// Indent of the blocks under a section heading
+const SECTION_INDENT: &str = "  ";
+
+/// A parameter of a command, as shown in its usage line
+pub struct UsageParam {
+    pub name: &'static str,
+    /// The parameter is the positional input rather than an option
+    pub positional: bool,
+    /// Describes the value, e.g. `<string>`
+    pub value: &'static str,
+    pub arity: Arity,
+}
+
+impl UsageParam {
+    fn synopsis(&self) -> String {
+        let mut synopsis = if self.positional {
+            format!("<{}>", self.name)
+        } else if self.arity.takes_value {
+            format!("--{} {}", self.name, self.value)
+        } else {
+            format!("--{}", self.name)
+        };
+
+        if self.arity.repeated {
+            synopsis.push_str("...");
+        }
+
+        if self.arity.required {
+            synopsis
+        } else {
+            format!("[{synopsis}]")
+        }
+    }
+}
+
+/// Builds the usage line of a command, with the options before the positional input
+pub fn usage(app: &str, command: &str, params: &[UsageParam]) -> String {
+    let options = params.iter().filter(|param| !param.positional);
+    let positional = params.iter().filter(|param| param.positional);
+
+    [app.to_string(), command.to_string()]
+        .into_iter()
+        .chain(options.chain(positional).map(UsageParam::synopsis))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Describes where an option's value can come from, e.g.
+/// `[default: 9] [env: APP_LEVEL=3] [possible values: fast, best]`, with the
+/// current value of the environment variable if it's set
+pub fn option_details(default: Option<&str>, env: Option<&str>, possible_values: &[&str]) -> String {
+    let mut details = vec![];
+
+    if let Some(default) = default {
+        details.push(format!("[default: {default}]"));
+    }
+
+    if let Some(env) = env {
+        match std::env::var(env) {
+            Ok(value) => details.push(format!("[env: {env}={value}]")),
+            Err(_) => details.push(format!("[env: {env}]")),
+        }
+    }
+
+    if !possible_values.is_empty() {
+        details.push(format!("[possible values: {}]", possible_values.join(", ")));
+    }
+
+    details.join(" ")
+}
+
+/// Wraps each line of `text` to `width` display columns, breaking at
+/// whitespace and indenting the wrapped lines by `indent`
+pub fn wrap(text: &str, width: usize, indent: &str) -> String {
+    let mut lines = vec![];
+
+    for paragraph in text.lines() {
+        let mut line = String::new();
+        let mut line_width = 0;
+        let mut line_empty = true;
+
+        for word in paragraph.split_whitespace() {
+            let word_width = word.width();
+            if !line_empty && line_width + 1 + word_width > width {
+                lines.push(line);
+                line = indent.to_string();
+                line_width = indent.width();
+                line_empty = true;
+            }
+
+            if !line_empty {
+                line.push(' ');
+                line_width += 1;
+            }
+
+            line.push_str(word);
+            line_width += word_width;
+            line_empty = false;
+        }
+
+        lines.push(line);
+    }
+
+    lines.join("\n")
+}
+
+/// The width left for the last column of a table, given the widths of the
+/// other columns, each padded by a space on either side
+fn last_column_width(widths: impl IntoIterator<Item = usize>, width: usize) -> usize {
+    let used: usize = widths.into_iter().map(|width| width + 2).sum();
+    width.saturating_sub(used + 2).max(MIN_COLUMN_WIDTH)
+}
+
+/// Wraps the last column of a table printed with `FORMAT_CLEAN` so the table
+/// fits in `width`, the cells of the last column lose their style
+#[cfg(feature = "prettytable")]
+pub fn wrap_last_column(table: &mut prettytable::Table, width: usize) {
+    let columns = table
+        .row_iter()
+        .map(|row| row.len())
+        .max()
+        .unwrap_or_default();
+    if columns == 0 {
+        return;
+    }
+
+    let widths = (0..columns - 1).map(|column| {
+        table
+            .column_iter(column)
+            .map(cell_width)
+            .max()
+            .unwrap_or_default()
+    });
+    let available = last_column_width(widths.collect::<Vec<_>>(), width);
+
+    for row in table.row_iter_mut() {
+        let last = columns - 1;
+        if let Some(cell) = row.get_cell(last) {
+            if cell_width(cell) > available {
+                let wrapped = wrap(&cell.get_content(), available, HANGING_INDENT);
+                _ = row.set_cell(prettytable::Cell::new(&wrapped), last);
+            }
+        }
+    }
+}
+
+/// An example invocation from the `# Examples` section of a command's docs
+#[derive(Clone, Debug)]
+pub struct Example {
+    pub command: &'static str,
+    pub explanation: &'static str,
+}
+
+/// Prints the examples of a command, highlighting the command lines
+pub fn print_examples(out: &mut Writer<'_>, theme: &Theme, examples: &[Example]) {
+    if examples.is_empty() {
+        return;
+    }
+
+    print_heading(out, theme, "Examples");
+    for Example { command, explanation } in examples {
+        _ = write!(out, "{SECTION_INDENT}");
+        _ = out.styled(theme.example, &format!("$ {command}"));
+        _ = writeln!(out);
+
+        if !explanation.is_empty() {
+            print_markdown(out, theme, explanation, "      ");
+        }
+    }
+}
+
+/// Prints a section of a command's docs with its heading
+pub fn print_section(out: &mut Writer<'_>, theme: &Theme, heading: &str, body: &str) {
+    print_heading(out, theme, heading);
+    print_markdown(out, theme, body, SECTION_INDENT);
+}
+
+fn print_heading(out: &mut Writer<'_>, theme: &Theme, heading: &str) {
+    _ = writeln!(out);
+    _ = out.styled(theme.heading, &format!("{heading}:"));
+    _ = writeln!(out);
+}
+
+#[cfg(feature = "prettytable")]
+fn cell_width(cell: &prettytable::Cell) -> usize {
+    cell.get_content()
+        .lines()
+        .map(UnicodeWidthStr::width)
+        .max()
+        .unwrap_or_default()
+}
+
+/// The app the help is for
+pub struct AppHelp<'a> {
+    pub name: &'a str,
+    pub version: &'a str,
+    pub about: &'a str,
+    /// The docs of the impl, shown in the long help
+    pub description: &'a str,
+}
+
+/// A command as described by its docs
+pub struct CommandHelp {
+    pub name: &'static str,
+    /// The heading the command is listed under
+    pub category: Option<&'static str>,
+    /// The first paragraph of the docs, used in the short help
+    pub summary: &'static str,
+    /// The docs up until the first section, used in the long help
+    pub description: &'static str,
+    pub options: Vec<OptionHelp>,
+    /// The sections of the docs after the description
+    pub sections: Vec<DocSection>,
+}
+
+/// A parameter of a command
+pub struct OptionHelp {
+    pub name: &'static str,
+    pub short: Option<&'static str>,
+    /// The parameter is the positional input rather than an option
+    pub positional: bool,
+    pub type_name: &'static str,
+    pub type_desc: &'static str,
+    pub type_extra: &'static str,
+    /// How the option is given, taking its default and env var into account
+    pub arity: Arity,
+    pub docs: &'static str,
+    /// The indented lines under the option, only shown in the long help
+    pub details: &'static str,
+    pub default: Option<&'static str>,
+    pub env: Option<&'static str>,
+    pub possible_values: &'static [&'static str],
+    /// The heading the option is listed under
+    pub section: Option<&'static str>,
+}
+
+impl OptionHelp {
+    /// The option as shown in the usage line
+    pub fn usage_param(&self) -> UsageParam {
+        UsageParam {
+            name: self.name,
+            positional: self.positional,
+            value: self.type_desc,
+            arity: self.arity,
+        }
+    }
+
+    /// How the option is written on the command line, e.g. `--level`
+    pub fn flag(&self) -> String {
+        if self.positional {
+            self.name.to_string()
+        } else {
+            format!("--{}", self.name)
+        }
+    }
+
+    /// The default, env var and possible values of the option
+    pub fn details(&self) -> String {
+        option_details(self.default, self.env, self.possible_values)
+    }
+}
+
+/// A section of a command's docs after its description
+pub enum DocSection {
+    Examples(Vec<Example>),
+    Text {
+        heading: &'static str,
+        body: &'static str,
+    },
+}
+
+/// What the help is shown for
+pub enum Page<'a> {
+    /// The list of every command
+    Commands(&'a [CommandHelp]),
+    Command(&'a CommandHelp),
+    /// The list of commands followed by the long help of each of them
+    All(&'a [CommandHelp]),
+    /// The commands and options that mention `term`
+    Search {
+        term: &'a str,
+        commands: &'a [CommandHelp],
+    },
+}
+
+/// The help that was asked for
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Topic {
+    /// `help`
+    Commands,
+    /// `help <command>` and `<command> --help`
+    Command(String),
+    /// `help --all`
+    All,
+    /// `help --search <term>`
+    Search(String),
+}
+
+impl Topic {
+    /// The page showing the topic, unknown commands fall back to the list
+    /// of commands
+    pub fn page<'a>(&'a self, commands: &'a [CommandHelp]) -> Page<'a> {
+        match self {
+            Topic::Commands => Page::Commands(commands),
+            Topic::Command(name) => commands
+                .iter()
+                .find(|command| command.name == name)
+                .map_or(Page::Commands(commands), Page::Command),
+            Topic::All => Page::All(commands),
+            Topic::Search(term) => Page::Search { term, commands },
+        }
+    }
+}
+
+/// How much help is shown with a usage error, set with
+/// `#[command(error_help = ..)]`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ErrorHelp {
+    /// The error, the usage line, a suggestion if there is one and how to get
+    /// the full help
+    #[default]
+    Compact,
+    /// The error followed by the whole help of the command
+    Full,
+}
+
+/// Everything shown in the help, laid out by a [`HelpRenderer`]
+pub struct Help<'a> {
+    pub app: AppHelp<'a>,
+    /// The error that caused the help to be shown
+    pub error: Option<&'a Error>,
+    /// `--help` was passed rather than `-h`
+    pub long: bool,
+    pub page: Page<'a>,
+    pub theme: Theme,
+}
+
+/// A cell of a table in the help
+#[derive(Clone, Debug, Default)]
+pub struct Cell {
+    pub text: String,
+    pub style: Style,
+}
+
+impl Cell {
+    pub fn new(text: impl Into<String>, style: Style) -> Self {
+        Self {
+            text: text.into(),
+            style,
+        }
+    }
+}
+
+/// Lays out the help, implementations only have to print tables, the rest
+/// of the layout can be changed by overriding `render`
+pub trait HelpRenderer {
+    /// Prints rows of cells as columns, the last column holds the
+    /// descriptions and is wrapped to the width of `out`
+    fn print_table(&self, out: &mut Writer<'_>, rows: &[Vec<Cell>]);
+
+    fn render(&self, out: &mut Writer<'_>, help: &Help<'_>) {
+        let app = &help.app;
+        let theme = &help.theme;
+        _ = out.styled(theme.heading, app.name);
+        _ = out.styled(theme.types, &format!(" {}", app.version));
+        _ = writeln!(out);
+
+        if !app.about.is_empty() {
+            _ = writeln!(out, "{}", app.about);
+        }
+
+        let listing = matches!(help.page, Page::Commands(_) | Page::All(_));
+        if help.long && listing && help.error.is_none() && !app.description.is_empty() {
+            _ = writeln!(out);
+            print_markdown(out, theme, app.description, "");
+        }
+
+        if let Some(error) = help.error {
+            _ = out.styled(theme.error, "Error");
+            _ = writeln!(out, ": {}", error);
+        }
+
+        _ = writeln!(out);
+
+        match help.page {
+            Page::Commands(commands) => self.render_commands(out, help, commands),
+            Page::Command(command) => self.render_command(out, help, command),
+            Page::All(commands) => self.render_all(out, help, commands),
+            Page::Search { term, commands } => self.render_search(out, help, term, commands),
+        }
+    }
+
+    /// Lists the commands under their categories
+    fn render_commands(&self, out: &mut Writer<'_>, help: &Help<'_>, commands: &[CommandHelp]) {
+        let groups = group_by_heading(commands, |command| command.category);
+        for (i, (category, commands)) in groups.into_iter().enumerate() {
+            if i > 0 {
+                _ = writeln!(out);
+            }
+
+            _ = out.styled(help.theme.heading, &format!("{}:", category.unwrap_or("Commands")));
+            _ = writeln!(out);
+
+            let rows = commands
+                .into_iter()
+                .map(|command| {
+                    vec![
+                        Cell::new(command.name, help.theme.command),
+                        Cell::new(strip_markdown(command.summary), Style::PLAIN),
+                    ]
+                })
+                .collect::<Vec<_>>();
+            self.print_table(out, &rows);
+        }
+    }
+
+    /// Shows the usage, docs and options of a command
+    fn render_command(&self, out: &mut Writer<'_>, help: &Help<'_>, command: &CommandHelp) {
+        let params = command.options.iter().map(OptionHelp::usage_param).collect::<Vec<_>>();
+        let theme = &help.theme;
+        _ = out.styled(theme.heading, "Usage:");
+        _ = writeln!(out, " {}\n", usage(help.app.name, command.name, &params));
+
+        _ = out.styled(theme.command, command.name);
+        let docs = if help.long { command.description } else { command.summary };
+        markdown::print_hanging(out, theme, docs, command.name.width(), " ", " ");
+
+        let sections = group_by_heading(&command.options, |option| option.section);
+        for (section, options) in sections {
+            if let Some(section) = section {
+                _ = writeln!(out);
+                _ = out.styled(theme.heading, &format!("{section}:"));
+                _ = writeln!(out);
+            }
+
+            let rows = options
+                .into_iter()
+                .map(|option| option_row(option, theme, help.long))
+                .collect::<Vec<_>>();
+            self.print_table(out, &rows);
+        }
+
+        for section in &command.sections {
+            match section {
+                DocSection::Examples(examples) if help.long => print_examples(out, theme, examples),
+                DocSection::Text { heading, body } if help.long => print_section(out, theme, heading, body),
+                DocSection::Examples(_) | DocSection::Text { .. } => {}
+            }
+        }
+    }
+
+    /// Shows a usage error with the usage line of the command it's about, a
+    /// suggestion if there is one and how to get the full help
+    fn render_error(&self, out: &mut Writer<'_>, help: &Help<'_>, error: &Error) {
+        let theme = &help.theme;
+        let app = help.app.name;
+        _ = out.styled(theme.error, "Error");
+        _ = writeln!(out, ": {}\n", error);
+
+        let (usage_line, more) = match help.page {
+            Page::Command(command) => {
+                let params = command.options.iter().map(OptionHelp::usage_param).collect::<Vec<_>>();
+                let more = format!("Run `{app} {} --help` for more", command.name);
+                (usage(app, command.name, &params), more)
+            }
+            _ => (format!("{app} <command>"), format!("Run `{app} help` to list the commands")),
+        };
+        _ = out.styled(theme.heading, "Usage:");
+        _ = writeln!(out, " {usage_line}");
+
+        if let Some(suggestion) = suggestion(&help.page, error) {
+            _ = writeln!(out, "\n{suggestion}");
+        }
+
+        _ = writeln!(out, "\n{more}");
+    }
+
+    /// Lists the commands, followed by the help of each of them
+    fn render_all(&self, out: &mut Writer<'_>, help: &Help<'_>, commands: &[CommandHelp]) {
+        self.render_commands(out, help, commands);
+        for command in commands {
+            _ = writeln!(out);
+            self.render_command(out, help, command);
+        }
+    }
+
+    /// Lists the commands and options that mention `term`, each with the
+    /// line of its docs that does
+    fn render_search(&self, out: &mut Writer<'_>, help: &Help<'_>, term: &str, commands: &[CommandHelp]) {
+        let found = search(commands, term);
+        if found.is_empty() {
+            _ = writeln!(out, "No commands or options match \"{term}\"");
+            return;
+        }
+
+        _ = out.styled(help.theme.heading, &format!("Matches for \"{term}\":"));
+        _ = writeln!(out);
+
+        let mut rows = vec![];
+        for Found { command, context, options } in found {
+            rows.push(vec![Cell::new(command.name, help.theme.command), Cell::new(context, Style::PLAIN)]);
+            for (option, context) in options {
+                let short = option.short.map(|short| format!(" -{short}")).unwrap_or_default();
+                rows.push(vec![
+                    Cell::new(format!("  {}{short}", option.flag()), help.theme.option),
+                    Cell::new(context, Style::PLAIN),
+                ]);
+            }
+        }
+        self.print_table(out, &rows);
+    }
+}
+
+/// A hint for fixing `error`: the command closest to an unknown one, or the
+/// values an option accepts
+fn suggestion(page: &Page<'_>, error: &Error) -> Option<String> {
+    match (error, page) {
+        (Error::UnknownCommand(name), Page::Commands(commands)) => {
+            let closest = closest(name, commands.iter().map(|command| command.name))?;
+            Some(format!("Did you mean `{closest}`?"))
+        }
+        (Error::InvalidType { arg, .. }, Page::Command(command)) => {
+            let option = command.options.iter().find(|option| option.name == arg)?;
+            let values = option.possible_values;
+            (!values.is_empty()).then(|| format!("Possible values: {}", values.join(", ")))
+        }
+        _ => None,
+    }
+}
+
+/// The candidate closest to `name`, if it's only a typo or two away
+fn closest<'a>(name: &str, candidates: impl IntoIterator<Item = &'a str>) -> Option<&'a str> {
+    let max = (name.chars().count() / 3).max(1);
+    candidates
+        .into_iter()
+        .map(|candidate| (edit_distance(name, candidate), candidate))
+        .filter(|(distance, _)| *distance <= max)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
+/// The number of characters to insert, remove, replace or swap with the next
+/// one to turn `a` into `b`
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a = a.chars().collect::<Vec<_>>();
+    let b = b.chars().collect::<Vec<_>>();
+    let mut distances = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in distances.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, distance) in distances[0].iter_mut().enumerate() {
+        *distance = j;
+    }
+
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let replace = distances[i - 1][j - 1] + usize::from(a[i - 1] != b[j - 1]);
+            let mut distance = replace.min(distances[i - 1][j] + 1).min(distances[i][j - 1] + 1);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distance = distance.min(distances[i - 2][j - 2] + 1);
+            }
+            distances[i][j] = distance;
+        }
+    }
+
+    distances[a.len()][b.len()]
+}
+
+/// A command that mentions a search term, with the options that do
+struct Found<'a> {
+    command: &'a CommandHelp,
+    /// The line of the docs that mentions the term, or the summary
+    context: String,
+    options: Vec<(&'a OptionHelp, String)>,
+}
+
+/// The commands whose name, docs or options mention `term`, ignoring case.
+/// Commands are also listed when only their options match
+fn search<'a>(commands: &'a [CommandHelp], term: &str) -> Vec<Found<'a>> {
+    let term = term.to_lowercase();
+    let matches = |text: &str| text.to_lowercase().contains(&term);
+
+    commands
+        .iter()
+        .filter_map(|command| {
+            let options = command
+                .options
+                .iter()
+                .filter_map(|option| {
+                    let context = if matches(option.name) {
+                        strip_markdown(option.docs)
+                    } else {
+                        matching_line(&[option.docs, option.details], &term)?
+                    };
+                    Some((option, context))
+                })
+                .collect::<Vec<_>>();
+
+            let mut docs = vec![command.description];
+            for section in &command.sections {
+                match section {
+                    DocSection::Examples(examples) => {
+                        docs.extend(examples.iter().flat_map(|example| [example.command, example.explanation]))
+                    }
+                    DocSection::Text { heading, body } => docs.extend([*heading, *body]),
+                }
+            }
+
+            let context = match matching_line(&docs, &term) {
+                _ if matches(command.name) => strip_markdown(command.summary),
+                Some(line) => line,
+                None if !options.is_empty() => strip_markdown(command.summary),
+                None => return None,
+            };
+            Some(Found { command, context, options })
+        })
+        .collect()
+}
+
+/// The first line of `texts` that contains the lowercase `term`, without markup
+fn matching_line(texts: &[&str], term: &str) -> Option<String> {
+    texts
+        .iter()
+        .flat_map(|text| text.lines())
+        .map(strip_markdown)
+        .find(|line| line.to_lowercase().contains(term))
+}
+
+/// The row of an option, the long version includes the type details and the
+/// extra lines of the option's docs
+fn option_row(option: &OptionHelp, theme: &Theme, long: bool) -> Vec<Cell> {
+    let name = theme.option;
+    let dim = theme.types;
+    let short = option.short.map(|short| format!("-{short}")).unwrap_or_default();
+    if long {
+        // The default, env and possible values go on their own line between
+        // the docs and the details
+        let docs = [strip_markdown(option.docs), option.details(), strip_markdown(option.details)]
+            .into_iter()
+            .filter(|line| !line.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        vec![
+            Cell::new(option.flag(), name),
+            Cell::new(short, name),
+            Cell::new(option.type_name, dim),
+            Cell::new(option.type_desc, dim),
+            Cell::new(option.type_extra, dim),
+            Cell::new(docs, Style::PLAIN),
+        ]
+    } else {
+        vec![
+            Cell::new(option.flag(), name),
+            Cell::new(short, name),
+            Cell::new(option.type_desc, dim),
+            Cell::new(strip_markdown(option.docs), Style::PLAIN),
+        ]
+    }
+}
+
+/// Groups items under their heading, in the order the headings first appear,
+/// with the items without a heading first
+fn group_by_heading<'a, T>(
+    items: &'a [T],
+    heading: impl Fn(&T) -> Option<&'a str>,
+) -> Vec<(Option<&'a str>, Vec<&'a T>)> {
+    let mut groups: Vec<(Option<&str>, Vec<&T>)> = vec![(None, vec![])];
+    for item in items {
+        let heading = heading(item);
+        match groups.iter_mut().find(|(group, _)| *group == heading) {
+            Some((_, group)) => group.push(item),
+            None => groups.push((heading, vec![item])),
+        }
+    }
+
+    groups
+}
+
+/// Wraps the last cell of each row to fit `width`, keeping the style
+fn wrap_rows(rows: &[Vec<Cell>], width: usize) -> Vec<Vec<Cell>> {
+    let columns = rows.iter().map(Vec::len).max().unwrap_or_default();
+    if columns == 0 {
+        return rows.to_vec();
+    }
+
+    let widths = (0..columns - 1).map(|column| {
+        rows.iter()
+            .filter_map(|row| row.get(column))
+            .map(|cell| text_width(&cell.text))
+            .max()
+            .unwrap_or_default()
+    });
+    let available = last_column_width(widths.collect::<Vec<_>>(), width);
+
+    rows.iter()
+        .map(|row| {
+            let mut row = row.clone();
+            if let Some(cell) = row.get_mut(columns - 1) {
+                if text_width(&cell.text) > available {
+                    cell.text = wrap(&cell.text, available, HANGING_INDENT);
+                }
+            }
+            row
+        })
+        .collect()
+}
+
+/// The width of the widest line of `text`
+fn text_width(text: &str) -> usize {
+    text.lines().map(UnicodeWidthStr::width).max().unwrap_or_default()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_wrap() {
+        assert_eq!(
+            wrap("the quick brown fox jumps over the lazy dog", 15, "  "),
+            "the quick brown\n  fox jumps\n  over the lazy\n  dog"
+        );
+        assert_eq!(wrap("short\nlines", 15, "  "), "short\nlines");
+    }
+
+    #[test]
+    fn test_wrap_wide_characters() {
+        // Each of these takes up two columns
+        assert_eq!(wrap("日本語 日本語 日本語", 14, ""), "日本語 日本語\n日本語");
+    }
+
+    #[test]
+    fn test_option_details() {
+        assert_eq!(option_details(None, None, &[]), "");
+        assert_eq!(
+            option_details(Some("9"), Some("ARGSTER_TEST_UNSET_LEVEL"), &["fast", "best"]),
+            "[default: 9] [env: ARGSTER_TEST_UNSET_LEVEL] [possible values: fast, best]"
+        );
+    }
+
+    #[test]
+    fn test_closest() {
+        let commands = ["install", "remove", "list"];
+        assert_eq!(closest("instal", commands), Some("install"));
+        assert_eq!(closest("uninstall", commands), Some("install"));
+        assert_eq!(closest("lsit", commands), Some("list"));
+        assert_eq!(closest("fetch", commands), None);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+    }
+
+    #[test]
+    fn test_usage() {
+        let params = [
+            UsageParam {
+                name: "input",
+                positional: true,
+                value: "<string>",
+                arity: Arity::REQUIRED,
+            },
+            UsageParam {
+                name: "times",
+                positional: false,
+                value: "<positive number>",
+                arity: Arity::REQUIRED.optional(),
+            },
+            UsageParam {
+                name: "loud",
+                positional: false,
+                value: "<true|false>",
+                arity: Arity::FLAG,
+            },
+            UsageParam {
+                name: "tag",
+                positional: false,
+                value: "<string>",
+                arity: Arity::REQUIRED.repeated(),
+            },
+        ];
+
+        assert_eq!(
+            usage("app", "hello", &params),
+            "app hello [--times <positive number>] [--loud] --tag <string>... <input>"
+        );
+    }
+}
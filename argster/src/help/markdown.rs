@@ -0,0 +1,396 @@
+//! Renders the markdown of doc comments as styled text
+//!
+//! Only the subset that reads well in a terminal is supported: paragraphs,
+//! which are reflowed, headings, lists, fenced code, and `**bold**`,
+//! `*italic*`, `` `code` `` and `[links](url)` inside the text. The markup
+//! itself is never printed, so without color the help is plain text.
+
+use super::MIN_COLUMN_WIDTH;
+use crate::{
+    style::{Style, Theme},
+    term::Writer,
+};
+use std::{io::Write, mem};
+use unicode_width::UnicodeWidthStr;
+
+/// Indent of lists and code blocks
+const BLOCK_INDENT: &str = "  ";
+
+/// A run of text in one style
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Span {
+    pub text: String,
+    pub style: Style,
+}
+
+impl Span {
+    pub fn new(text: impl Into<String>, style: Style) -> Self {
+        Self {
+            text: text.into(),
+            style,
+        }
+    }
+}
+
+/// A block of a doc comment, with its lines joined
+#[derive(Debug, PartialEq, Eq)]
+enum Block {
+    Heading(String),
+    Paragraph(String),
+    Item { indent: usize, marker: String, text: String },
+    Code(Vec<String>),
+}
+
+/// Prints markdown `text`, every line indented by `indent` and wrapped to
+/// the width of `out`
+pub fn print_markdown(out: &mut Writer<'_>, theme: &Theme, text: &str, indent: &str) {
+    print_hanging(out, theme, text, 0, indent, indent);
+}
+
+/// Prints markdown `text` starting on the current line, which already has
+/// `written` columns, after `first`, the following lines are indented by
+/// `indent`. The line is ended even if there's no text
+pub(crate) fn print_hanging(out: &mut Writer<'_>, theme: &Theme, text: &str, written: usize, first: &str, indent: &str) {
+    let width = out
+        .width()
+        .map(|width| width.saturating_sub(indent.width()).max(MIN_COLUMN_WIDTH));
+    let offset = (written + first.width()).saturating_sub(indent.width());
+    let lines = layout_after(text, theme, width, offset);
+    if lines.is_empty() {
+        _ = writeln!(out);
+    }
+
+    for (i, line) in lines.iter().enumerate() {
+        if !line.is_empty() {
+            _ = write!(out, "{}", if i == 0 { first } else { indent });
+        }
+        for span in line {
+            _ = out.styled(span.style, &span.text);
+        }
+        _ = writeln!(out);
+    }
+}
+
+/// The text of markdown on a single line without the markup, for table cells
+pub fn strip_markdown(text: &str) -> String {
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    spans(&text, &Theme::default())
+        .into_iter()
+        .map(|span| span.text)
+        .collect()
+}
+
+/// Lays out markdown `text` as lines no wider than `width`, blocks that are
+/// apart in the docs are separated by an empty line
+pub fn layout(text: &str, theme: &Theme, width: Option<usize>) -> Vec<Vec<Span>> {
+    layout_after(text, theme, width, 0)
+}
+
+/// Lays out markdown `text` like `layout`, with the first line starting
+/// `offset` columns in because something was already written before it
+fn layout_after(text: &str, theme: &Theme, width: Option<usize>, offset: usize) -> Vec<Vec<Span>> {
+    let mut lines = vec![];
+    for (i, (gap, block)) in blocks(text).into_iter().enumerate() {
+        if i > 0 && gap {
+            lines.push(vec![]);
+        }
+        let offset = if i == 0 { offset } else { 0 };
+
+        match block {
+            Block::Heading(text) => {
+                let mut spans = spans(&text, theme);
+                for span in &mut spans {
+                    span.style = span.style.bold();
+                }
+                lines.extend(wrap_spans(&spans, width, offset));
+            }
+            Block::Paragraph(text) => lines.extend(wrap_spans(&spans(&text, theme), width, offset)),
+            Block::Item { indent, marker, text } => {
+                let prefix = format!("{BLOCK_INDENT}{}{marker} ", " ".repeat(indent));
+                let hanging = " ".repeat(prefix.width());
+                let width = width.map(|width| width.saturating_sub(prefix.width()).max(MIN_COLUMN_WIDTH));
+                for (i, mut line) in wrap_spans(&spans(&text, theme), width, offset).into_iter().enumerate() {
+                    let prefix = if i == 0 { &prefix } else { &hanging };
+                    line.insert(0, Span::new(prefix, Style::PLAIN));
+                    lines.push(line);
+                }
+            }
+            Block::Code(code) => lines.extend(code.into_iter().map(|line| {
+                vec![
+                    Span::new(BLOCK_INDENT, Style::PLAIN),
+                    Span::new(line, theme.code),
+                ]
+            })),
+        }
+    }
+
+    lines
+}
+
+/// Splits the lines of `text` into blocks, each with whether an empty line
+/// came before it
+fn blocks(text: &str) -> Vec<(bool, Block)> {
+    let lines = text.lines().map(str::trim_end).collect::<Vec<_>>();
+    // Doc comments usually start with a space after `///`
+    let base = lines
+        .iter()
+        .filter(|line| !line.is_empty())
+        .map(|line| line.len() - line.trim_start().len())
+        .min()
+        .unwrap_or_default();
+
+    let mut blocks = vec![];
+    let mut code: Option<Vec<String>> = None;
+    let mut gap = false;
+    // Whether the last block continues on the next line
+    let mut open = false;
+    for line in lines {
+        let line = line.get(base..).unwrap_or_default();
+        let trimmed = line.trim_start();
+
+        if let Some(lines) = &mut code {
+            if trimmed.starts_with("```") {
+                blocks.push((mem::take(&mut gap), Block::Code(mem::take(lines))));
+                code = None;
+            } else {
+                lines.push(line.to_string());
+            }
+            continue;
+        }
+
+        if trimmed.starts_with("```") {
+            code = Some(vec![]);
+            open = false;
+        } else if trimmed.is_empty() {
+            gap = !blocks.is_empty();
+            open = false;
+        } else if let Some(heading) = heading(trimmed) {
+            blocks.push((mem::take(&mut gap), Block::Heading(heading.to_string())));
+            open = false;
+        } else if let Some((marker, text)) = list_item(trimmed) {
+            let indent = line.len() - trimmed.len();
+            blocks.push((mem::take(&mut gap), Block::Item { indent, marker, text: text.to_string() }));
+            open = true;
+        } else {
+            match blocks.last_mut() {
+                Some((_, Block::Paragraph(text) | Block::Item { text, .. })) if open => {
+                    text.push(' ');
+                    text.push_str(trimmed);
+                }
+                _ => blocks.push((mem::take(&mut gap), Block::Paragraph(trimmed.to_string()))),
+            }
+            open = true;
+        }
+    }
+
+    if let Some(lines) = code {
+        blocks.push((gap, Block::Code(lines)));
+    }
+
+    blocks
+}
+
+/// The text of a `#` heading
+fn heading(line: &str) -> Option<&str> {
+    let text = line.trim_start_matches('#');
+    (text.len() < line.len() && text.starts_with(' ')).then(|| text.trim())
+}
+
+/// The marker and text of a list item, bullets are shown as `-`
+fn list_item(line: &str) -> Option<(String, &str)> {
+    if let Some(text) = ["- ", "* ", "+ "].iter().find_map(|bullet| line.strip_prefix(bullet)) {
+        return Some(("-".to_string(), text.trim_start()));
+    }
+
+    let digits = line.len() - line.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+    let text = line[digits..].strip_prefix(". ")?;
+    (digits > 0).then(|| (line[..=digits].to_string(), text.trim_start()))
+}
+
+/// Splits a line of markdown into spans, removing the markup
+fn spans(text: &str, theme: &Theme) -> Vec<Span> {
+    let mut spans = vec![];
+    let mut plain = String::new();
+    let mut rest = text;
+    let mut previous = None;
+
+    while let Some(c) = rest.chars().next() {
+        match styled_span(rest, previous, theme) {
+            Some((styled, len)) => {
+                if !plain.is_empty() {
+                    spans.push(Span::new(mem::take(&mut plain), Style::PLAIN));
+                }
+                spans.extend(styled);
+                previous = rest[..len].chars().last();
+                rest = &rest[len..];
+            }
+            None => {
+                plain.push(c);
+                previous = Some(c);
+                rest = &rest[c.len_utf8()..];
+            }
+        }
+    }
+
+    if !plain.is_empty() {
+        spans.push(Span::new(plain, Style::PLAIN));
+    }
+
+    spans
+}
+
+/// The spans of the markup at the start of `text`, and the length of the
+/// markup. `previous` is the character before `text`
+fn styled_span(text: &str, previous: Option<char>, theme: &Theme) -> Option<(Vec<Span>, usize)> {
+    if let Some(code) = text.strip_prefix('`') {
+        let end = code.find('`').filter(|end| *end > 0)?;
+        return Some((vec![Span::new(&code[..end], theme.code)], end + 2));
+    }
+
+    // An `_` inside a word, like in snake_case, isn't markup
+    if text.starts_with('_') && previous.is_some_and(char::is_alphanumeric) {
+        return None;
+    }
+
+    for marker in ["**", "__"] {
+        if let Some(inner) = text.strip_prefix(marker) {
+            let end = closing(inner, marker)?;
+            return Some((vec![Span::new(&inner[..end], Style::PLAIN.bold())], end + 4));
+        }
+    }
+
+    for marker in ["*", "_"] {
+        if let Some(inner) = text.strip_prefix(marker) {
+            let end = closing(inner, marker)?;
+            return Some((vec![Span::new(&inner[..end], Style::PLAIN.italic())], end + 2));
+        }
+    }
+
+    let (label, rest) = text.strip_prefix('[')?.split_once("](")?;
+    let end = rest.find(')')?;
+    let url = &rest[..end];
+    if label.is_empty() || label.contains(['[', ']']) {
+        return None;
+    }
+
+    let mut spans = vec![Span::new(label, Style::PLAIN)];
+    if url != label {
+        spans.push(Span::new(format!(" ({url})"), theme.types));
+    }
+    Some((spans, label.len() + end + 4))
+}
+
+/// Where `marker` closes an emphasis that starts at the start of `text`,
+/// emphasis can't start or end with a space
+fn closing(text: &str, marker: &str) -> Option<usize> {
+    if text.starts_with(char::is_whitespace) {
+        return None;
+    }
+
+    let end = text.find(marker).filter(|end| *end > 0)?;
+    let after = text[end + marker.len()..].chars().next();
+    let inside_word = marker.starts_with('_') && after.is_some_and(char::is_alphanumeric);
+    (!text[..end].ends_with(char::is_whitespace) && !inside_word).then_some(end)
+}
+
+/// Reflows spans into lines no wider than `width`, breaking at spaces
+fn wrap_spans(spans: &[Span], width: Option<usize>, offset: usize) -> Vec<Vec<Span>> {
+    let mut words: Vec<Vec<Span>> = vec![];
+    let mut word = vec![];
+    for span in spans {
+        for (i, part) in span.text.split(char::is_whitespace).enumerate() {
+            if i > 0 && !word.is_empty() {
+                words.push(mem::take(&mut word));
+            }
+            if !part.is_empty() {
+                word.push(Span::new(part, span.style));
+            }
+        }
+    }
+    if !word.is_empty() {
+        words.push(word);
+    }
+
+    let mut lines = vec![];
+    let mut line: Vec<Span> = vec![];
+    // The first line starts after the `offset` columns already written
+    let mut line_width = offset;
+    for word in words {
+        let word_width = word.iter().map(|span| span.text.width()).sum::<usize>();
+        let space = usize::from(!line.is_empty());
+        if line_width > 0 && width.is_some_and(|width| line_width + space + word_width > width) {
+            // Without room for a first word after the offset, the first line is left empty
+            lines.push(mem::take(&mut line));
+            line_width = 0;
+        } else if !line.is_empty() {
+            line.push(Span::new(" ", Style::PLAIN));
+            line_width += 1;
+        }
+
+        line.extend(word);
+        line_width += word_width;
+    }
+    if !line.is_empty() {
+        lines.push(line);
+    }
+
+    lines
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn plain(text: &str, width: Option<usize>) -> String {
+        plain_after(text, width, 0)
+    }
+
+    fn plain_after(text: &str, width: Option<usize>, offset: usize) -> String {
+        layout_after(text, &Theme::default(), width, offset)
+            .into_iter()
+            .map(|line| line.into_iter().map(|span| span.text).collect::<String>())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn test_spans() {
+        let theme = Theme::default();
+        assert_eq!(
+            spans("Run **all** `tests` *now*", &theme),
+            [
+                Span::new("Run ", Style::PLAIN),
+                Span::new("all", Style::PLAIN.bold()),
+                Span::new(" ", Style::PLAIN),
+                Span::new("tests", theme.code),
+                Span::new(" ", Style::PLAIN),
+                Span::new("now", Style::PLAIN.italic()),
+            ]
+        );
+        assert_eq!(strip_markdown("see [the docs](https://docs.rs)"), "see the docs (https://docs.rs)");
+        assert_eq!(strip_markdown("snake_case_name and 2 * 3 * 4"), "snake_case_name and 2 * 3 * 4");
+        assert_eq!(strip_markdown("an _unclosed emphasis"), "an _unclosed emphasis");
+    }
+
+    #[test]
+    fn test_layout() {
+        let text = " Copies files\n from **a** to `b`.\n\n - one\n - two\n   continued\n   1. nested\n\n ```\n let a = 1;\n ```";
+        assert_eq!(
+            plain(text, None),
+            "Copies files from a to b.\n\n  - one\n  - two continued\n    1. nested\n\n  let a = 1;"
+        );
+        assert_eq!(plain(" # Notes\n word word word", Some(9)), "Notes\nword word\nword");
+    }
+
+    #[test]
+    fn test_layout_wraps_items() {
+        let text = "- a list item that is too long for a line";
+        assert_eq!(plain(text, Some(24)), "  - a list item that is\n    too long for a line");
+    }
+
+    #[test]
+    fn test_layout_after() {
+        assert_eq!(plain_after("word word word", Some(9), 4), "word\nword word");
+        assert_eq!(plain_after("longword", Some(9), 4), "\nlongword");
+    }
+}
@@ -0,0 +1,54 @@
+//! A renderer without any dependencies, aligning the columns with spaces
+
+use super::{text_width, wrap_rows, Cell, HelpRenderer};
+use crate::term::Writer;
+use std::io::Write;
+
+/// Lays out the tables of the help the same way as the default renderer,
+/// without depending on `prettytable`
+#[derive(Clone, Copy, Debug, Default)]
+pub struct PlainRenderer;
+
+impl HelpRenderer for PlainRenderer {
+    fn print_table(&self, out: &mut Writer<'_>, rows: &[Vec<Cell>]) {
+        let rows = match out.width() {
+            Some(width) => wrap_rows(rows, width),
+            None => rows.to_vec(),
+        };
+        let columns = rows.iter().map(Vec::len).max().unwrap_or_default();
+        let widths = (0..columns)
+            .map(|column| {
+                rows.iter()
+                    .filter_map(|row| row.get(column))
+                    .map(|cell| text_width(&cell.text))
+                    .max()
+                    .unwrap_or_default()
+            })
+            .collect::<Vec<_>>();
+
+        for row in &rows {
+            let lines = row
+                .iter()
+                .map(|cell| cell.text.lines().collect::<Vec<_>>())
+                .collect::<Vec<_>>();
+            let height = lines.iter().map(Vec::len).max().unwrap_or_default().max(1);
+
+            for i in 0..height {
+                for (column, cell) in row.iter().enumerate() {
+                    let line = lines[column].get(i).copied().unwrap_or_default();
+                    _ = write!(out, " ");
+                    if !line.is_empty() {
+                        _ = out.styled(cell.style, line);
+                    }
+
+                    // Like a table without a right border, the last column isn't filled
+                    if column + 1 < columns {
+                        _ = write!(out, "{}", " ".repeat(widths[column] - text_width(line)));
+                    }
+                    _ = write!(out, " ");
+                }
+                _ = writeln!(out);
+            }
+        }
+    }
+}
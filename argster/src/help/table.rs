@@ -0,0 +1,36 @@
+//! The default renderer, drawing the tables with `prettytable`
+
+use super::{Cell, HelpRenderer};
+use crate::term::Writer;
+use prettytable::{format::consts::FORMAT_CLEAN, Attr, Row, Table};
+
+/// Draws the tables of the help with `prettytable`
+#[derive(Clone, Copy, Debug, Default)]
+pub struct TableRenderer;
+
+impl HelpRenderer for TableRenderer {
+    fn print_table(&self, out: &mut Writer<'_>, rows: &[Vec<Cell>]) {
+        let mut table = Table::init(
+            rows.iter()
+                .map(|row| Row::new(row.iter().map(styled_cell).collect()))
+                .collect(),
+        );
+        table.set_format(*FORMAT_CLEAN);
+        _ = out.print_table(&table);
+    }
+}
+
+fn styled_cell(cell: &Cell) -> prettytable::Cell {
+    let mut styled = prettytable::Cell::new(&cell.text);
+    if cell.style.bold {
+        styled.style(Attr::Bold);
+    }
+    if cell.style.italic {
+        styled.style(Attr::Italic(true));
+    }
+    if let Some(color) = cell.style.fg {
+        styled.style(Attr::ForegroundColor(color.number()));
+    }
+
+    styled
+}
@@ -1,11 +1,44 @@
 pub use argster_macros::command;
+#[cfg(feature = "prettytable")]
 pub use prettytable;
 use std::collections::HashMap;
-pub mod term {
-    pub use term::{color, stderr, Attr};
-}
 
 pub mod from_args;
+pub mod help;
+pub mod rt;
+pub mod style;
+pub mod term;
+
+/// The result of running a command line in process with `run_from`
+#[derive(Debug)]
+pub struct Outcome {
+    /// `Ok` if a command ran or help was requested
+    pub result: Result<(), from_args::Error>,
+    pub exit_code: i32,
+    /// The help or error text that was rendered
+    pub output: String,
+    /// Whether the output was colored, resolved from `--color` as if it was
+    /// written to a pipe
+    pub color: bool,
+}
+
+impl Outcome {
+    /// The exit code used when the command line could not be parsed
+    pub const USAGE_ERROR: i32 = 2;
+
+    pub fn new(result: Result<(), from_args::Error>, output: Vec<u8>) -> Self {
+        Self {
+            exit_code: if result.is_ok() { 0 } else { Self::USAGE_ERROR },
+            result,
+            output: String::from_utf8_lossy(&output).into_owned(),
+            color: false,
+        }
+    }
+
+    pub fn is_success(&self) -> bool {
+        self.result.is_ok()
+    }
+}
 
 #[derive(Debug, PartialEq)]
 pub enum ArgsItem {
@@ -0,0 +1,208 @@
+//! Styles of the text in help, independent of the terminal library
+
+/// One of the 16 standard terminal colors
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Color {
+    Black,
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+    White,
+    BrightBlack,
+    BrightRed,
+    BrightGreen,
+    BrightYellow,
+    BrightBlue,
+    BrightMagenta,
+    BrightCyan,
+    BrightWhite,
+}
+
+impl Color {
+    /// The number of the color in the terminal's palette
+    pub const fn number(self) -> u32 {
+        self as u32
+    }
+}
+
+/// How a piece of help text is styled
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Style {
+    pub bold: bool,
+    pub italic: bool,
+    pub fg: Option<Color>,
+}
+
+impl Style {
+    /// Text without any styling
+    pub const PLAIN: Style = Style {
+        bold: false,
+        italic: false,
+        fg: None,
+    };
+
+    pub const fn bold(self) -> Style {
+        Style { bold: true, ..self }
+    }
+
+    pub const fn italic(self) -> Style {
+        Style { italic: true, ..self }
+    }
+
+    pub const fn fg(self, color: Color) -> Style {
+        Style {
+            fg: Some(color),
+            ..self
+        }
+    }
+
+    pub fn is_plain(&self) -> bool {
+        *self == Style::PLAIN
+    }
+
+    /// Parses `;` separated SGR codes, e.g. `1;32` for bold green, `3` is italic
+    pub fn from_sgr(codes: &str) -> Option<Style> {
+        const COLORS: [Color; 16] = [
+            Color::Black,
+            Color::Red,
+            Color::Green,
+            Color::Yellow,
+            Color::Blue,
+            Color::Magenta,
+            Color::Cyan,
+            Color::White,
+            Color::BrightBlack,
+            Color::BrightRed,
+            Color::BrightGreen,
+            Color::BrightYellow,
+            Color::BrightBlue,
+            Color::BrightMagenta,
+            Color::BrightCyan,
+            Color::BrightWhite,
+        ];
+
+        let mut style = Style::PLAIN;
+        for code in codes.split(';') {
+            match code.trim().parse::<usize>().ok()? {
+                0 => style = Style::PLAIN,
+                1 => style.bold = true,
+                3 => style.italic = true,
+                code @ 30..=37 => style.fg = Some(COLORS[code - 30]),
+                code @ 90..=97 => style.fg = Some(COLORS[code - 90 + 8]),
+                _ => return None,
+            }
+        }
+
+        Some(style)
+    }
+}
+
+/// Setting this env var overrides parts of the theme, e.g.
+/// `command=1;34:error=35`, see [`Theme::with_overrides`]
+pub const THEME_ENV: &str = "ARGSTER_COLORS";
+
+/// The styles of each part of the help
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Theme {
+    /// Headings, the app name and the usage label
+    pub heading: Style,
+    pub command: Style,
+    pub option: Style,
+    /// The type descriptions of options, and the version
+    pub types: Style,
+    pub error: Style,
+    /// The command lines of examples
+    pub example: Style,
+    /// Code spans and blocks in the docs
+    pub code: Style,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            heading: Style::PLAIN.bold(),
+            command: Style::PLAIN.bold().fg(Color::BrightGreen),
+            option: Style::PLAIN.bold().fg(Color::BrightGreen),
+            types: Style::PLAIN.fg(Color::BrightBlack),
+            error: Style::PLAIN.bold().fg(Color::BrightRed),
+            example: Style::PLAIN.fg(Color::BrightCyan),
+            code: Style::PLAIN.fg(Color::Yellow),
+        }
+    }
+}
+
+impl Theme {
+    /// Applies overrides written like `LS_COLORS`, `part=codes` pairs
+    /// separated by `:`, where the codes are `;` separated SGR codes, `1` for
+    /// bold, `3` for italic, `30`-`37` and `90`-`97` for colors and `0` for no
+    /// style. The parts are `heading`, `command`, `option`, `types`, `error`,
+    /// `example` and `code`, anything that can't be parsed is ignored
+    pub fn with_overrides(mut self, overrides: &str) -> Self {
+        for (part, codes) in overrides.split(':').filter_map(|pair| pair.split_once('=')) {
+            let Some(style) = Style::from_sgr(codes) else {
+                continue;
+            };
+
+            match part.trim() {
+                "heading" => self.heading = style,
+                "command" => self.command = style,
+                "option" => self.option = style,
+                "types" => self.types = style,
+                "error" => self.error = style,
+                "example" => self.example = style,
+                "code" => self.code = style,
+                _ => (),
+            }
+        }
+
+        self
+    }
+
+    /// Applies the overrides in `ARGSTER_COLORS`, if it's set
+    pub fn with_env_overrides(self) -> Self {
+        match std::env::var(THEME_ENV) {
+            Ok(overrides) => self.with_overrides(&overrides),
+            Err(_) => self,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_from_sgr() {
+        assert_eq!(Style::from_sgr("1;32"), Some(Style::PLAIN.bold().fg(Color::Green)));
+        assert_eq!(Style::from_sgr("94"), Some(Style::PLAIN.fg(Color::BrightBlue)));
+        assert_eq!(Style::from_sgr("0"), Some(Style::PLAIN));
+        assert_eq!(Style::from_sgr("4"), None);
+        assert_eq!(Style::from_sgr("bold"), None);
+    }
+
+    #[test]
+    fn test_theme_overrides() {
+        let theme = Theme::default().with_overrides("command=1;34:error=35:bogus=1:types=x");
+
+        assert_eq!(theme.command, Style::PLAIN.bold().fg(Color::Blue));
+        assert_eq!(theme.error, Style::PLAIN.fg(Color::Magenta));
+        assert_eq!(theme.types, Theme::default().types);
+    }
+
+    #[test]
+    fn test_overrides_keep_custom_theme() {
+        let custom = Theme {
+            option: Style::PLAIN.fg(Color::Magenta),
+            types: Style::PLAIN.italic(),
+            ..Theme::default()
+        };
+
+        let theme = custom.with_overrides("option=33:types=bogus");
+
+        assert_eq!(theme.option, Style::PLAIN.fg(Color::Yellow));
+        assert_eq!(theme.types, custom.types);
+    }
+}
@@ -0,0 +1,599 @@
+//! Output used when printing help and errors
+#[cfg(feature = "prettytable")]
+use crate::help::wrap_last_column;
+use crate::{
+    from_args::{Error, FromArgsItem},
+    style::Style,
+    ArgsItem,
+};
+#[cfg(feature = "prettytable")]
+use prettytable::Table;
+use std::{
+    cell::Cell,
+    env,
+    ffi::OsString,
+    io::{self, IsTerminal, Write},
+    process::{Command, Stdio},
+    str::FromStr,
+};
+#[cfg(feature = "term")]
+use term::{terminfo::TermInfo, TerminfoTerminal};
+#[cfg(feature = "term")]
+pub use term::{color, stderr, Attr, Terminal};
+
+/// The width help is wrapped to when it isn't printed to a terminal
+pub const DEFAULT_WIDTH: usize = 80;
+
+/// The width of an output, `COLUMNS` takes priority over the size of the
+/// terminal, and `None` is returned if it isn't a terminal and `COLUMNS` isn't set
+fn output_width(terminal: bool) -> Option<usize> {
+    if let Some(columns) = env::var("COLUMNS").ok().and_then(|x| x.parse().ok()) {
+        return Some(columns);
+    }
+
+    if !terminal {
+        return None;
+    }
+
+    terminal_size::terminal_size().map(|(terminal_size::Width(width), _)| width as usize)
+}
+
+/// Whether help, errors and the output of commands should be colored, set
+/// with `--color=auto|always|never`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ColorChoice {
+    /// Color terminals, unless `NO_COLOR` is set or `CLICOLOR_FORCE` forces it
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorChoice {
+    /// Resolves the choice for a stream, e.g. `ColorChoice::Auto.enabled(&io::stdout())`
+    pub fn enabled(self, stream: &impl IsTerminal) -> bool {
+        self.resolve(stream.is_terminal())
+    }
+
+    fn resolve(self, is_terminal: bool) -> bool {
+        self.resolve_with(is_terminal, |name| env::var_os(name))
+    }
+
+    /// Resolves the choice, reading env vars with `var`. Any `NO_COLOR`
+    /// disables color, while `CLICOLOR_FORCE=0` doesn't force it
+    fn resolve_with(self, is_terminal: bool, var: impl Fn(&str) -> Option<OsString>) -> bool {
+        let no_color = var("NO_COLOR").is_some_and(|value| !value.is_empty());
+        let force = var("CLICOLOR_FORCE").is_some_and(|value| !value.is_empty() && value != "0");
+        match self {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            ColorChoice::Auto if no_color => false,
+            ColorChoice::Auto if force => true,
+            ColorChoice::Auto => is_terminal,
+        }
+    }
+}
+
+thread_local! {
+    static COLOR_CHOICE: Cell<ColorChoice> = const { Cell::new(ColorChoice::Auto) };
+}
+
+/// The choice passed with `--color` to the command line being run, so
+/// commands can color their output like the help, e.g.
+/// `color_choice().enabled(&io::stdout())`. It's `Auto` outside of `run_with`
+/// and on threads other than the one running the command
+pub fn color_choice() -> ColorChoice {
+    COLOR_CHOICE.get()
+}
+
+/// Sets `color_choice()` while a command line runs, restoring the previous
+/// choice when it's dropped so runs don't leak into each other
+pub struct ColorChoiceScope {
+    previous: ColorChoice,
+}
+
+impl ColorChoiceScope {
+    pub fn enter(choice: ColorChoice) -> Self {
+        Self {
+            previous: COLOR_CHOICE.replace(choice),
+        }
+    }
+}
+
+impl Drop for ColorChoiceScope {
+    fn drop(&mut self) {
+        COLOR_CHOICE.set(self.previous);
+    }
+}
+
+impl FromStr for ColorChoice {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "auto" => Ok(ColorChoice::Auto),
+            "always" => Ok(ColorChoice::Always),
+            "never" => Ok(ColorChoice::Never),
+            _ => Err(format!("unknown color choice '{s}', expected auto, always or never")),
+        }
+    }
+}
+
+impl FromArgsItem for ColorChoice {
+    const TYPE_NAME: &'static str = "color";
+    const TYPE_DESC: &'static str = "<auto|always|never>";
+    const POSSIBLE_VALUES: &'static [&'static str] = &["auto", "always", "never"];
+
+    fn from_args_item(item: Option<&ArgsItem>) -> Result<Self, Error> {
+        match item {
+            Some(ArgsItem::String(s)) => s.parse().map_err(|_| Error::InvalidType {
+                arg: "color".into(),
+                expected: "auto|always|never".into(),
+                found: s.clone(),
+            }),
+            Some(_) => Err(Error::InvalidType {
+                arg: "color".into(),
+                expected: "auto|always|never".into(),
+                found: "flag".into(),
+            }),
+            None => Err(Error::NotFound("color".into())),
+        }
+    }
+}
+
+/// The flags given before the command, that configure the output rather
+/// than a command
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct GlobalFlags {
+    /// The choice passed with `--color`
+    pub color: Option<ColorChoice>,
+    /// `--no-pager` was passed
+    pub no_pager: bool,
+}
+
+/// Setting this env var disables the pager, same as passing `--no-pager`
+pub const NO_PAGER_ENV: &str = "ARGSTER_NO_PAGER";
+
+/// The pager used when `PAGER` isn't set
+const DEFAULT_PAGER: &str = "less -R";
+
+/// The height of the terminal stdout is connected to
+fn terminal_height() -> Option<usize> {
+    if !io::stdout().is_terminal() {
+        return None;
+    }
+
+    terminal_size::terminal_size().map(|(_, terminal_size::Height(height))| height as usize)
+}
+
+/// The command that runs `pager` through the shell, like git does, so
+/// quotes and arguments in `PAGER` work
+fn pager_command(pager: &str) -> Command {
+    #[cfg(windows)]
+    let (shell, flag) = ("cmd", "/C");
+    #[cfg(not(windows))]
+    let (shell, flag) = ("sh", "-c");
+
+    let mut command = Command::new(shell);
+    command.arg(flag).arg(pager);
+    command
+}
+
+/// The user's pager, from `PAGER`
+fn pager() -> String {
+    env::var("PAGER")
+        .ok()
+        .filter(|pager| !pager.trim().is_empty())
+        .unwrap_or_else(|| DEFAULT_PAGER.to_string())
+}
+
+/// Pipes `content` through `pager`, waiting for it to exit. An error means
+/// the content wasn't shown, e.g. the shell couldn't find the pager
+fn spawn_pager(pager: &str, content: &[u8]) -> io::Result<()> {
+    let mut child = pager_command(pager).stdin(Stdio::piped()).spawn()?;
+
+    let written = match child.stdin.take() {
+        Some(mut stdin) => stdin.write_all(content),
+        None => Ok(()),
+    };
+    let status = child.wait()?;
+
+    match written {
+        // A pager that is quit early closes its input, which is only a
+        // failure if it didn't exit cleanly, like a shell without the pager
+        Err(ex) if ex.kind() != io::ErrorKind::BrokenPipe || !status.success() => Err(ex),
+        _ if !status.success() => Err(io::Error::other(format!("{pager} exited with {status}"))),
+        _ => Ok(()),
+    }
+}
+
+enum Output<'a> {
+    /// A stream that can be styled using the terminfo of the environment
+    #[cfg(feature = "term")]
+    Terminal(Box<TerminfoTerminal<Box<dyn Write + 'a>>>),
+    Plain(Box<dyn Write + 'a>),
+}
+
+impl<'a> Output<'a> {
+    /// Styles `writer` if the terminfo of the environment can be found
+    fn styled(writer: Box<dyn Write + 'a>) -> Self {
+        #[cfg(feature = "term")]
+        if let Ok(info) = TermInfo::from_env() {
+            return Output::Terminal(Box::new(TerminfoTerminal::new_with_terminfo(writer, info)));
+        }
+
+        Output::Plain(writer)
+    }
+}
+
+/// Where help and errors are written to, either a terminal that can be
+/// styled, or a plain writer that ignores styling
+pub struct Writer<'a> {
+    output: Output<'a>,
+    /// Output that was asked for, like help, moves to stdout
+    standard: bool,
+    width: Option<usize>,
+    /// The width was set with `with_width`, rather than from the output
+    fixed_width: bool,
+    pager: bool,
+    /// The output is a terminal, used to resolve `ColorChoice::Auto`
+    terminal: bool,
+    choice: ColorChoice,
+    color: bool,
+}
+
+impl<'a> Writer<'a> {
+    /// Writes to `writer`, styled if the terminfo of the environment can be
+    /// found, and wrapped to the terminal's width if `terminal` is set
+    fn stream(writer: impl Write + 'a, terminal: bool) -> Self {
+        Self {
+            output: Output::styled(Box::new(writer)),
+            standard: false,
+            width: Some(output_width(terminal).unwrap_or(DEFAULT_WIDTH)),
+            fixed_width: false,
+            pager: env::var_os(NO_PAGER_ENV).is_none() && io::stdout().is_terminal(),
+            terminal,
+            choice: ColorChoice::Auto,
+            color: ColorChoice::Auto.resolve(terminal),
+        }
+    }
+
+    /// Writes to stderr, wrapping to the terminal's width, long help is
+    /// shown in a pager if stdout is a terminal
+    pub fn stderr() -> Self {
+        Self::stream(io::stderr(), io::stderr().is_terminal())
+    }
+
+    /// Writes to stdout, wrapping to the terminal's width, long help is
+    /// shown in a pager if stdout is a terminal
+    pub fn stdout() -> Self {
+        Self::stream(io::stdout(), io::stdout().is_terminal())
+    }
+
+    /// Writes errors to stderr, and help or the version to stdout when
+    /// they're asked for so they can be piped, see `requested_output`
+    pub fn standard() -> Self {
+        Self {
+            standard: true,
+            ..Self::stderr()
+        }
+    }
+
+    /// Called before writing output that was asked for, like help, moves a
+    /// `standard` writer to stdout, keeping the width and color if they were set
+    pub fn requested_output(&mut self) {
+        if !self.standard {
+            return;
+        }
+
+        let terminal = io::stdout().is_terminal();
+        self.output = Output::styled(Box::new(io::stdout()));
+        self.standard = false;
+        self.terminal = terminal;
+        if !self.fixed_width {
+            self.width = Some(output_width(terminal).unwrap_or(DEFAULT_WIDTH));
+        }
+        self.set_color_choice(self.choice);
+    }
+
+    /// Writes to `writer` without any styling or wrapping, e.g. to capture help in tests
+    pub fn plain(writer: impl Write + 'a) -> Self {
+        Self {
+            output: Output::Plain(Box::new(writer)),
+            standard: false,
+            width: None,
+            fixed_width: false,
+            pager: false,
+            terminal: false,
+            choice: ColorChoice::Auto,
+            color: false,
+        }
+    }
+
+    /// Enables or disables showing long help in a pager
+    pub fn with_pager(self, pager: bool) -> Self {
+        Self { pager, ..self }
+    }
+
+    /// Wraps tables to `width` columns
+    pub fn with_width(self, width: usize) -> Self {
+        Self {
+            width: Some(width),
+            fixed_width: true,
+            ..self
+        }
+    }
+
+    /// Colors the output regardless of the environment, until a `--color` is applied
+    pub fn with_color(self, color: bool) -> Self {
+        let choice = if color { ColorChoice::Always } else { ColorChoice::Never };
+        Self { choice, color, ..self }
+    }
+
+    pub fn width(&self) -> Option<usize> {
+        self.width
+    }
+
+    /// Whether styles are written, as resolved from the color choice
+    pub fn color(&self) -> bool {
+        self.color
+    }
+
+    fn set_color_choice(&mut self, choice: ColorChoice) {
+        self.choice = choice;
+        self.color = choice.resolve(self.terminal);
+    }
+
+    /// Applies the global flags given before the command
+    pub fn apply_flags(&mut self, flags: &GlobalFlags) {
+        if let Some(choice) = flags.color {
+            self.set_color_choice(choice);
+        }
+        if flags.no_pager {
+            self.pager = false;
+        }
+    }
+
+    /// Runs `write` against a buffer, showing the result in a pager if it
+    /// doesn't fit in the terminal, otherwise it is written out directly
+    pub fn paged(&mut self, write: impl FnOnce(&mut Writer<'_>)) {
+        if !self.pager {
+            return write(self);
+        }
+
+        let mut content = Vec::new();
+        let mut page = Writer {
+            output: Output::styled(Box::new(&mut content)),
+            standard: false,
+            width: self.width,
+            fixed_width: self.fixed_width,
+            pager: false,
+            terminal: self.terminal,
+            choice: self.choice,
+            color: self.color,
+        };
+        write(&mut page);
+        drop(page);
+
+        let lines = content.iter().filter(|byte| **byte == b'\n').count();
+        let too_tall = terminal_height().is_some_and(|height| lines >= height);
+        if too_tall {
+            self.page(&pager(), &content);
+        } else {
+            _ = self.write_all(&content);
+        }
+    }
+
+    /// Shows `content` in `pager`, writing it out directly if the pager fails
+    fn page(&mut self, pager: &str, content: &[u8]) {
+        if spawn_pager(pager, content).is_err() {
+            _ = self.write_all(content);
+        }
+    }
+
+    /// Styles the text written after it, until `reset`
+    pub fn style(&mut self, style: Style) -> io::Result<()> {
+        if !self.color || style.is_plain() {
+            return Ok(());
+        }
+
+        match &mut self.output {
+            #[cfg(feature = "term")]
+            Output::Terminal(terminal) => {
+                if style.bold {
+                    terminal.attr(Attr::Bold).map_err(io::Error::other)?;
+                }
+                if style.italic {
+                    terminal.attr(Attr::Italic(true)).map_err(io::Error::other)?;
+                }
+                if let Some(color) = style.fg {
+                    terminal.fg(color.number()).map_err(io::Error::other)?;
+                }
+                Ok(())
+            }
+            Output::Plain(_) => Ok(()),
+        }
+    }
+
+    /// Writes `text` in `style`
+    pub fn styled(&mut self, style: Style, text: &str) -> io::Result<()> {
+        self.style(style)?;
+        self.write_all(text.as_bytes())?;
+        if style.is_plain() {
+            Ok(())
+        } else {
+            self.reset()
+        }
+    }
+
+    #[cfg(feature = "term")]
+    pub fn attr(&mut self, attr: Attr) -> term::Result<()> {
+        if !self.color {
+            return Ok(());
+        }
+
+        match &mut self.output {
+            Output::Terminal(terminal) => terminal.attr(attr),
+            Output::Plain(_) => Ok(()),
+        }
+    }
+
+    #[cfg(feature = "term")]
+    pub fn fg(&mut self, color: color::Color) -> term::Result<()> {
+        if !self.color {
+            return Ok(());
+        }
+
+        match &mut self.output {
+            Output::Terminal(terminal) => terminal.fg(color),
+            Output::Plain(_) => Ok(()),
+        }
+    }
+
+    pub fn reset(&mut self) -> io::Result<()> {
+        if !self.color {
+            return Ok(());
+        }
+
+        match &mut self.output {
+            #[cfg(feature = "term")]
+            Output::Terminal(terminal) => terminal.reset().map_err(io::Error::other),
+            Output::Plain(_) => Ok(()),
+        }
+    }
+
+    /// Prints the table, wrapping its last column to fit the width of the writer
+    #[cfg(feature = "prettytable")]
+    pub fn print_table(&mut self, table: &Table) -> io::Result<usize> {
+        let mut table = table.clone();
+        if let Some(width) = self.width {
+            wrap_last_column(&mut table, width);
+        }
+
+        match &mut self.output {
+            Output::Terminal(terminal) if self.color => table.print_term(terminal.as_mut()),
+            Output::Terminal(terminal) => table.print(terminal),
+            Output::Plain(writer) => table.print(writer),
+        }
+    }
+}
+
+impl Write for Writer<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match &mut self.output {
+            #[cfg(feature = "term")]
+            Output::Terminal(terminal) => terminal.write(buf),
+            Output::Plain(writer) => writer.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match &mut self.output {
+            #[cfg(feature = "term")]
+            Output::Terminal(terminal) => terminal.flush(),
+            Output::Plain(writer) => writer.flush(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_apply_flags() {
+        let mut out = Writer::plain(io::sink());
+
+        out.apply_flags(&GlobalFlags::default());
+        assert!(!out.color());
+
+        out.apply_flags(&GlobalFlags {
+            color: Some(ColorChoice::Always),
+            ..Default::default()
+        });
+        assert!(out.color());
+
+        let mut out = Writer::plain(io::sink()).with_pager(true);
+        out.apply_flags(&GlobalFlags {
+            no_pager: true,
+            ..Default::default()
+        });
+        assert!(!out.pager);
+    }
+
+    #[test]
+    fn test_color_choice_scope() {
+        {
+            let _scope = ColorChoiceScope::enter(ColorChoice::Never);
+            assert_eq!(color_choice(), ColorChoice::Never);
+        }
+        assert_eq!(color_choice(), ColorChoice::Auto);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_pager_command() {
+        let output = pager_command("printf '%s|' 'two words'").output().unwrap();
+        assert_eq!(output.stdout, b"two words|");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_page_falls_back() {
+        let mut output = Vec::new();
+        let mut out = Writer::plain(&mut output);
+        out.page("definitely-not-a-pager 2>/dev/null", b"help\n");
+        out.page("exit 1", b"more\n");
+        out.page("cat >/dev/null", b"paged\n");
+        drop(out);
+
+        assert_eq!(output, b"help\nmore\n");
+    }
+
+    #[test]
+    fn test_requested_output() {
+        let mut output = Vec::new();
+        let mut out = Writer::plain(&mut output);
+        out.requested_output();
+        _ = write!(out, "help");
+        drop(out);
+        assert_eq!(output, b"help");
+
+        let mut out = Writer::standard().with_pager(false);
+        out.requested_output();
+        assert!(!out.standard);
+        assert!(!out.pager);
+
+        let mut out = Writer::standard().with_width(30).with_color(true);
+        out.requested_output();
+        assert_eq!(out.width(), Some(30));
+        assert!(out.color());
+
+        let mut out = Writer::standard();
+        out.apply_flags(&GlobalFlags {
+            color: Some(ColorChoice::Never),
+            ..Default::default()
+        });
+        out.requested_output();
+        assert!(!out.color());
+    }
+
+    #[test]
+    fn test_color_choice() {
+        assert!(ColorChoice::Always.resolve(false));
+        assert!(!ColorChoice::Never.resolve(true));
+        assert_eq!("never".parse(), Ok(ColorChoice::Never));
+        assert!("blue".parse::<ColorChoice>().is_err());
+
+        let env = |vars: &'static [(&'static str, &'static str)]| {
+            move |name: &str| vars.iter().find(|(var, _)| *var == name).map(|(_, value)| OsString::from(value))
+        };
+        assert!(!ColorChoice::Auto.resolve_with(true, env(&[("NO_COLOR", "0")])));
+        assert!(ColorChoice::Auto.resolve_with(true, env(&[("NO_COLOR", "")])));
+        assert!(ColorChoice::Auto.resolve_with(false, env(&[("CLICOLOR_FORCE", "1")])));
+        assert!(!ColorChoice::Auto.resolve_with(false, env(&[("CLICOLOR_FORCE", "0")])));
+
+        let item = ArgsItem::String("blue".into());
+        assert!(matches!(ColorChoice::from_args_item(Some(&item)), Err(Error::InvalidType { .. })));
+    }
+}
@@ -0,0 +1,832 @@
+use argster::command;
+
+#[derive(Default)]
+struct Counter {
+    total: u32,
+    greeting: String,
+}
+
+#[command]
+impl Counter {
+    /// Adds a number to the total
+    /// # Args
+    /// input The number to add
+    fn add(&mut self, input: u32) {
+        self.total += input;
+    }
+
+    /// Greets someone using the configured greeting
+    /// # Args
+    /// input The name to greet
+    fn greet(&self, input: String) {
+        println!("{} {input}", self.greeting);
+    }
+}
+
+#[test]
+fn dispatches_to_instance_methods() {
+    let mut counter = Counter {
+        greeting: "Hello".into(),
+        ..Default::default()
+    };
+
+    counter.run_from(["add", "2"]);
+    counter.run_from(["add", "3"]);
+    counter.run_from(["greet", "world"]);
+
+    assert_eq!(counter.total, 5);
+}
+
+#[derive(Default)]
+struct Store {
+    items: Vec<String>,
+}
+
+impl Store {
+    async fn fetch(name: &str) -> String {
+        format!("fetched {name}")
+    }
+}
+
+#[command]
+impl Store {
+    /// Fetches an item and stores it
+    /// # Args
+    /// input The item to fetch
+    async fn get(&mut self, input: String) {
+        let item = Self::fetch(&input).await;
+        self.items.push(item);
+    }
+
+    /// Stores an item directly
+    /// # Args
+    /// input The item to store
+    fn put(&mut self, input: String) {
+        self.items.push(input);
+    }
+}
+
+#[test]
+fn runs_async_and_sync_commands() {
+    let mut store = Store::default();
+
+    store.run_from(["get", "apple"]);
+    store.run_from(["put", "pear"]);
+
+    assert_eq!(store.items, ["fetched apple", "pear"]);
+}
+
+#[test]
+fn parses_without_running() {
+    let mut counter = Counter::default();
+
+    let invocation = Counter::try_parse_from(["add", "4"]).unwrap();
+    assert!(matches!(invocation, CounterCommand::Add { input: 4 }));
+    assert_eq!(counter.total, 0);
+
+    counter.dispatch(invocation);
+    assert_eq!(counter.total, 4);
+
+    assert!(matches!(
+        Counter::try_parse_from(["remove", "4"]),
+        Err(argster::from_args::Error::UnknownCommand(command)) if command == "remove"
+    ));
+}
+
+mod shop {
+    use argster::command;
+
+    pub struct Shop;
+
+    #[command(vis = pub, derive(Debug, Clone, PartialEq))]
+    impl Shop {
+        /// Buys an item
+        /// # Args
+        /// input The item to buy
+        /// --count -c How many to buy
+        fn buy(input: String, count: Option<u32>) {
+            let _ = (input, count);
+        }
+    }
+}
+
+#[test]
+fn exposes_invocation_enum() {
+    let invocation = shop::Shop::try_parse_from(["buy", "apple", "-c", "2"]).unwrap();
+    assert_eq!(
+        format!("{invocation:?}"),
+        r#"Buy { input: "apple", count: Some(2) }"#
+    );
+    assert!(invocation.clone() == shop::ShopCommand::Buy { input: "apple".into(), count: Some(2) });
+    shop::Shop::dispatch(invocation);
+}
+
+#[test]
+fn captures_help_and_errors() {
+    let mut counter = Counter::default();
+
+    let outcome = counter.run_from(["help"]);
+    assert!(outcome.is_success());
+    assert_eq!(outcome.exit_code, 0);
+    assert!(outcome.output.contains("Commands:"));
+    assert!(outcome.output.contains(" add    Adds a number to the total"));
+
+    let outcome = counter.run_from(["add", "many"]);
+    assert!(!outcome.is_success());
+    assert_eq!(outcome.exit_code, argster::Outcome::USAGE_ERROR);
+    assert!(outcome.output.contains(
+        "Error: Expected input to be of type <positive number>, but found string: invalid digit found in string"
+    ));
+    assert_eq!(counter.total, 0);
+}
+
+struct Gated;
+
+#[command]
+impl Gated {
+    /// Always available
+    fn stable() {}
+
+    /// Never compiled into the tests
+    #[cfg(not(test))]
+    fn experimental() {
+        this_does_not_exist();
+    }
+}
+
+#[test]
+fn skips_cfg_disabled_commands() {
+    assert!(Gated::try_parse_from(["stable"]).is_ok());
+    assert!(matches!(
+        Gated::try_parse_from(["experimental"]),
+        Err(argster::from_args::Error::UnknownCommand(_))
+    ));
+    assert!(!Gated::run_from(["help"]).output.contains("experimental"));
+}
+
+struct Config {
+    verbose: bool,
+}
+
+#[command]
+impl Config {
+    /// Prints the config
+    fn show() {
+        println!("verbose: {}", Self::load().verbose);
+    }
+
+    #[command(skip)]
+    fn load() -> Config {
+        Config { verbose: true }
+    }
+
+    #[command(skip)]
+    fn describe(config: &Config) -> String {
+        format!("verbose: {}", config.verbose)
+    }
+}
+
+#[test]
+fn skips_helper_methods() {
+    assert_eq!(Config::describe(&Config::load()), "verbose: true");
+    assert!(matches!(
+        Config::try_parse_from(["load"]),
+        Err(argster::from_args::Error::UnknownCommand(_))
+    ));
+    assert!(!Config::run_from(["help"]).output.contains("load"));
+}
+
+struct Naming;
+
+#[command]
+impl Naming {
+    /// Runs everything without changing anything
+    /// # Args
+    /// --skip-tests -s Don't run the tests
+    fn dry_run_all(skip_tests: bool, #[command(rename = "out")] output_dir: Option<String>) {
+        assert!(skip_tests);
+        assert_eq!(output_dir.as_deref(), Some("target"));
+    }
+
+    #[command(rename = "ls")]
+    fn list_all() {}
+}
+
+struct SnakeNaming;
+
+#[command(rename_all = "snake_case")]
+impl SnakeNaming {
+    fn dry_run() {}
+}
+
+#[test]
+fn renames_commands_and_options() {
+    assert!(Naming::run_from(["dry-run-all", "--skip-tests", "--out", "target"]).is_success());
+    assert!(Naming::run_from(["dry-run-all", "-s", "--out", "target"]).is_success());
+    assert!(Naming::try_parse_from(["ls"]).is_ok());
+    assert!(Naming::try_parse_from(["dry_run_all"]).is_err());
+
+    let help = Naming::run_from(["help", "dry-run-all"]).output;
+    assert!(help.contains("--skip-tests"));
+    assert!(help.contains("--out"));
+
+    assert!(SnakeNaming::try_parse_from(["dry_run"]).is_ok());
+}
+
+struct Tool;
+
+/// Manages the widgets in the current directory
+///
+/// Widgets are stored in `.widgets`.
+#[command(name = "widget", version = "2.0.0", about = "Widget management")]
+impl Tool {
+    /// Lists the widgets
+    fn list() {}
+}
+
+#[test]
+fn uses_app_info_from_attributes() {
+    let help = Tool::run_from(["help"]).output;
+    assert!(help.starts_with(
+        "widget 2.0.0\nWidget management\n\nManages the widgets in the current directory\n\nWidgets are stored in .widgets.\n"
+    ));
+
+    let error = Tool::run_from(["remove"]).output;
+    assert!(error.starts_with("Error: Unknown command remove\n"));
+    assert!(error.contains("Run `widget help` to list the commands\n"));
+    assert!(!error.contains("Manages the widgets"));
+
+    let help = Counter::default().run_from(["help"]).output;
+    assert!(help.starts_with(concat!(
+        env!("CARGO_PKG_NAME"),
+        " ",
+        env!("CARGO_PKG_VERSION")
+    )));
+}
+
+struct Versioned;
+
+#[command(name = "versioned", version = "1.2.3", commit = "abc1234", build_date = "2026-01-01")]
+impl Versioned {
+    /// Does nothing
+    fn noop() {}
+}
+
+#[test]
+fn prints_version() {
+    for args in [&["--version"][..], &["-V"], &["version"]] {
+        let outcome = Versioned::run_from(args.iter().copied());
+        assert!(outcome.is_success());
+        assert_eq!(outcome.output, "versioned 1.2.3\n");
+    }
+
+    assert_eq!(
+        Versioned::run_from(["version", "--verbose"]).output,
+        "versioned 1.2.3\ncommit: abc1234\nbuild-date: 2026-01-01\n"
+    );
+}
+
+struct Installer;
+
+#[command(version = "0.3.0")]
+impl Installer {
+    /// Installs a package
+    /// # Args
+    /// input The package to install
+    /// --version -v The version of the package
+    fn install(input: String, version: Option<String>) {
+        assert_eq!(input, "serde");
+        assert_eq!(version.as_deref(), Some("1.0"));
+    }
+
+    /// Removes a package
+    /// # Args
+    /// input The package to remove
+    fn remove(input: String) {
+        panic!("removed {input}");
+    }
+}
+
+#[test]
+fn handles_builtin_flags_per_command() {
+    for flag in ["--help", "-h"] {
+        let outcome = Installer::run_from(["remove", "serde", flag]);
+        assert!(outcome.is_success());
+        assert!(outcome.output.contains("Removes a package"));
+    }
+
+    let outcome = Installer::run_from(["remove", "--version"]);
+    assert_eq!(outcome.output, "argster 0.3.0\n");
+
+    // install has its own --version option, so the flag goes to the command
+    assert!(Installer::run_from(["install", "serde", "--version", "1.0"]).is_success());
+
+    let outcome = Installer::run_from(["help", "uninstall"]);
+    assert_eq!(outcome.exit_code, argster::Outcome::USAGE_ERROR);
+    assert!(outcome.output.contains("Error: Unknown command uninstall"));
+}
+
+#[test]
+fn shows_all_help_and_searches_it() {
+    let outcome = Installer::run_from(["help", "--all"]);
+    assert!(outcome.is_success());
+    assert!(outcome.output.contains("Commands:"));
+    assert!(outcome.output.contains("Usage: argster install [--version <string>] <input>\n"));
+    assert!(outcome.output.contains("Usage: argster remove <input>\n"));
+    assert!(outcome.output.contains("The package to remove"));
+
+    let output = Installer::run_from(["help", "--search", "VERSION"]).output;
+    assert!(output.contains("Matches for \"VERSION\":\n"));
+    assert!(output.contains("--version -v"));
+    assert!(output.contains("The version of the package"));
+    assert!(!output.contains("remove"));
+
+    let output = Installer::run_from(["help", "--search", "removes"]).output;
+    assert!(output.contains("remove"));
+    assert!(output.contains("Removes a package"));
+    assert!(!output.contains("install"));
+
+    let output = Installer::run_from(["help", "--search", "nothing"]).output;
+    assert!(output.contains("No commands or options match \"nothing\"\n"));
+
+    let outcome = Installer::run_from(["help", "--search"]);
+    assert_eq!(outcome.exit_code, argster::Outcome::USAGE_ERROR);
+}
+
+#[test]
+fn shows_usage_line() {
+    let outcome = Installer::run_from(["help", "install"]);
+    assert!(outcome
+        .output
+        .contains("Usage: argster install [--version <string>] <input>\n"));
+
+    let outcome = Installer::run_from(["remove"]);
+    assert!(outcome.output.contains("Usage: argster remove <input>\n"));
+}
+
+struct Documented;
+
+#[command]
+impl Documented {
+    /// Copies files
+    ///
+    /// Copies are done in parallel, and existing files are only
+    /// overwritten with --force.
+    /// # Args
+    /// input The files to copy
+    /// --force -f Overwrite existing files
+    ///     Files that are newer than the source are overwritten too.
+    fn copy(input: Vec<String>, force: bool) {
+        let _ = (input, force);
+    }
+}
+
+#[test]
+fn shows_short_and_long_help() {
+    let short = Documented::run_from(["copy", "-h"]).output;
+    assert!(short.contains("copy Copies files\n"));
+    assert!(short.contains("Overwrite existing files"));
+    assert!(!short.contains("in parallel"));
+    assert!(!short.contains("newer than the source"));
+    assert!(!short.contains("list"));
+
+    for args in [&["copy", "--help"][..], &["help", "copy"]] {
+        let long = Documented::run_from(args.iter().copied()).output;
+        assert!(long.contains("copy Copies files\n\n Copies are done in parallel"));
+        assert!(long.contains("Files that are newer than the source are overwritten too."));
+        assert!(long.contains("list"));
+    }
+}
+
+struct Marked;
+
+#[command]
+impl Marked {
+    /// Syncs the **local** copy
+    ///
+    /// Pulls the changes from `origin`, see
+    /// [the guide](https://example.com/sync) for the details:
+    /// - *fast forwards* only
+    /// - keeps local_only files
+    /// # Args
+    /// --dry-run Shows the `diff` without syncing
+    fn sync(dry_run: bool) {
+        let _ = dry_run;
+    }
+}
+
+#[test]
+fn renders_markdown_in_help() {
+    let output = Marked::run_from(["help"]).output;
+    assert!(output.contains("sync  Syncs the local copy \n"));
+
+    let output = Marked::run_from(["sync", "--help"]).output;
+    assert!(output.contains(
+        "sync Syncs the local copy\n\n Pulls the changes from origin, see the guide (https://example.com/sync) for the details:\n   - fast forwards only\n   - keeps local_only files\n"
+    ));
+    assert!(output.contains("Shows the diff without syncing"));
+}
+
+struct Wrapped;
+
+#[command]
+impl Wrapped {
+    /// Syncs the local copy with the remote, fetching and merging the changes of its branch
+    /// # Args
+    /// --remote -r The remote to sync with, defaults to the upstream of the current branch
+    fn sync(remote: Option<String>) {
+        let _ = remote;
+    }
+}
+
+#[test]
+fn wraps_help_to_width() {
+    let mut output = Vec::new();
+    let mut out = argster::term::Writer::plain(&mut output).with_width(60);
+    Wrapped::run_with(["sync", "-h"], &mut out).unwrap();
+    drop(out);
+
+    let output = String::from_utf8(output).unwrap();
+    // The first line of the docs follows the command name
+    assert!(output.contains("sync Syncs the local copy with the remote, fetching and\n merging the changes of its branch\n"));
+    assert!(output.contains(
+        " --remote  -r  <string>  The remote to sync with, defaults \n                           to the upstream of the current \n                           branch \n"
+    ));
+}
+
+struct Archiver;
+
+#[command]
+impl Archiver {
+    /// Packs files into an archive
+    /// # Examples
+    /// $ archiver pack notes.txt --level 9
+    /// Packs the notes with the best compression
+    /// $ archiver --color never pack notes.txt -h
+    /// Shows the help of pack without color
+    /// # Notes
+    /// Hidden files are skipped.
+    /// # Args
+    /// input The files to pack
+    /// --level -l The compression level
+    fn pack(input: Vec<String>, level: Option<u32>) {
+        let _ = (input, level);
+    }
+}
+
+#[test]
+fn shows_doc_sections_in_help() {
+    let short = Archiver::run_from(["pack", "-h"]).output;
+    assert!(short.contains("pack Packs files into an archive\n"));
+    assert!(short.contains("The compression level"));
+    assert!(!short.contains("Examples:"));
+    assert!(!short.contains("Hidden files"));
+
+    let long = Archiver::run_from(["pack", "--help"]).output;
+    assert!(!long.contains("# Examples"));
+    assert!(long.contains("Examples:\n  $ archiver pack notes.txt --level 9\n      Packs the notes with the best compression\n"));
+    assert!(long.contains("Notes:\n  Hidden files are skipped.\n"));
+}
+
+struct Stale;
+
+#[command]
+impl Stale {
+    /// Resizes an image
+    /// # Examples
+    /// $ stale resize photo.png --width 640
+    /// $ stale resize photo.png --width wide
+    /// # Args
+    /// input The image to resize
+    /// --width -w The new width
+    fn resize(input: String, width: Option<u32>) {
+        let _ = (input, width);
+    }
+}
+
+struct Renamed;
+
+#[command]
+impl Renamed {
+    /// Crops an image
+    /// # Examples
+    /// $ renamed crop photo.png --top 10
+    /// # Args
+    /// input The image to crop
+    fn crop(input: String, height: Option<u32>) {
+        let _ = (input, height);
+    }
+}
+
+#[test]
+fn verifies_examples() {
+    assert!(Archiver::verify_examples().is_ok());
+
+    // The commands themselves still run, only the examples are stale
+    assert!(Stale::run_from(["resize", "photo.png", "--width", "640"]).is_success());
+    assert!(Renamed::run_from(["crop", "photo.png", "--height", "10"]).is_success());
+
+    let (example, error) = Stale::verify_examples().unwrap_err();
+    assert_eq!(example, "stale resize photo.png --width wide");
+    assert!(matches!(error, argster::from_args::Error::InvalidType { .. }));
+
+    let (example, error) = Renamed::verify_examples().unwrap_err();
+    assert_eq!(example, "renamed crop photo.png --top 10");
+    assert!(matches!(error, argster::from_args::Error::UnknownOption(option) if option == "top"));
+}
+
+struct Grouped;
+
+#[command]
+impl Grouped {
+    /// Shows the status
+    fn status() {}
+
+    /// Fetches from a remote
+    /// # Args
+    /// --remote The remote to fetch from
+    /// --timeout The seconds to wait
+    /// --quiet -q Print nothing
+    #[command(category = "Network")]
+    fn fetch(
+        #[command(section = "Network options")] remote: Option<String>,
+        #[command(section = "Network options")] timeout: Option<u32>,
+        #[command(section = "Output options")] quiet: bool,
+    ) {
+        let _ = (remote, timeout, quiet);
+    }
+
+    /// Pushes to a remote
+    #[command(category = "Network")]
+    fn push() {}
+
+    /// Commits the changes
+    #[command(category = "Changes")]
+    fn commit() {}
+}
+
+#[test]
+fn groups_commands_and_options() {
+    let output = Grouped::run_from(["help"]).output;
+    let status = output.find("Commands:\n status").unwrap();
+    let network = output.find("Network:\n fetch").unwrap();
+    let push = output.find(" push").unwrap();
+    let changes = output.find("Changes:\n commit").unwrap();
+    assert!(status < network && network < push && push < changes);
+
+    let output = Grouped::run_from(["fetch", "-h"]).output;
+    let network = output.find("Network options:\n --remote").unwrap();
+    let timeout = output.find(" --timeout").unwrap();
+    let quiet = output.find("Output options:\n --quiet  -q").unwrap();
+    assert!(network < timeout && timeout < quiet);
+}
+
+#[derive(Debug, PartialEq)]
+enum Speed {
+    Fast,
+    Best,
+}
+
+impl argster::from_args::FromArgsItem for Speed {
+    const TYPE_DESC: &'static str = "<speed>";
+    const TYPE_NAME: &'static str = "Speed";
+    const POSSIBLE_VALUES: &'static [&'static str] = &["fast", "best"];
+
+    fn from_args_item(item: Option<&argster::ArgsItem>) -> Result<Self, argster::from_args::Error> {
+        match String::from_args_item(item)?.as_str() {
+            "fast" => Ok(Speed::Fast),
+            "best" => Ok(Speed::Best),
+            other => Err(argster::from_args::Error::InvalidType {
+                arg: "".into(),
+                expected: "fast|best".into(),
+                found: other.into(),
+            }),
+        }
+    }
+}
+
+struct Compressor;
+
+#[command]
+impl Compressor {
+    /// Compresses a file
+    /// # Args
+    /// input The file to compress
+    /// --level -l The compression level
+    /// --threads The number of threads
+    /// --speed How to trade speed for size
+    fn compress(
+        input: String,
+        #[command(default = "6")] level: u32,
+        #[command(env = "ARGSTER_TEST_THREADS", default = "1")] threads: u32,
+        speed: Option<Speed>,
+    ) {
+        let _ = (input, level, threads, speed);
+    }
+}
+
+#[test]
+fn uses_defaults_and_env_vars() {
+    let Ok(CompressorCommand::Compress { level, threads, .. }) = Compressor::try_parse_from(["compress", "a.txt"]) else {
+        panic!("expected compress");
+    };
+    assert_eq!((level, threads), (6, 1));
+
+    // Setting the env var is tested in tests/env_vars.rs, before any threads start
+    let Ok(CompressorCommand::Compress { level, speed, .. }) =
+        Compressor::try_parse_from(["compress", "a.txt", "-l", "9", "--speed", "best"])
+    else {
+        panic!("expected compress");
+    };
+    assert_eq!((level, speed), (9, Some(Speed::Best)));
+
+    let short = Compressor::run_from(["compress", "-h"]).output;
+    assert!(short.contains("[--level <positive number>]"));
+    assert!(short.contains("The compression level"));
+    assert!(!short.contains("[default: 6]"));
+    assert!(!short.contains("[possible values"));
+
+    let long = Compressor::run_from(["compress", "--help"]).output;
+    assert!(long.contains("The compression level \n"));
+    assert!(long.contains("[default: 6]"));
+    assert!(long.contains("[default: 1] [env: ARGSTER_TEST_THREADS]"));
+    assert!(long.contains("[possible values: fast, best]"));
+}
+
+#[test]
+fn shows_compact_usage_errors() {
+    let outcome = Compressor::run_from(["compress", "a.txt", "--speed", "slow"]);
+    assert_eq!(outcome.exit_code, argster::Outcome::USAGE_ERROR);
+    assert_eq!(
+        outcome.output,
+        "Error: Expected speed to be of type fast|best, but found slow\n\n\
+         Usage: argster compress [--level <positive number>] [--threads <positive number>] [--speed <speed>] <input>\n\n\
+         Possible values: fast, best\n\n\
+         Run `argster compress --help` for more\n"
+    );
+
+    let output = Compressor::run_from(["compres"]).output;
+    assert!(output.contains("Usage: argster <command>\n\nDid you mean `compress`?\n"));
+
+    let output = Compressor::run_from(Vec::<String>::new()).output;
+    assert!(output.contains("Commands:\n"));
+}
+
+struct Verbose;
+
+#[command(error_help = argster::help::ErrorHelp::Full)]
+impl Verbose {
+    /// Deletes a file
+    /// # Args
+    /// input The file to delete
+    fn delete(input: String) {
+        let _ = input;
+    }
+}
+
+#[test]
+fn shows_full_help_on_errors_when_configured() {
+    let outcome = Verbose::run_from(["delete"]);
+    assert_eq!(outcome.exit_code, argster::Outcome::USAGE_ERROR);
+    assert!(outcome.output.contains("Error: Required argument input not found\n"));
+    assert!(outcome.output.contains("delete Deletes a file\n"));
+    assert!(outcome.output.contains("The file to delete"));
+    assert!(!outcome.output.contains("Run `"));
+}
+
+struct Listing;
+
+impl argster::help::HelpRenderer for Listing {
+    fn print_table(&self, out: &mut argster::term::Writer<'_>, rows: &[Vec<argster::help::Cell>]) {
+        use std::io::Write;
+
+        for row in rows {
+            let cells = row.iter().map(|cell| cell.text.trim()).collect::<Vec<_>>();
+            _ = writeln!(out, "- {}", cells.join(" | "));
+        }
+    }
+}
+
+struct Rendered;
+
+#[command(renderer = Listing)]
+impl Rendered {
+    /// Lists the files
+    /// # Args
+    /// --all -a Include hidden files
+    fn list(all: bool) {
+        let _ = all;
+    }
+}
+
+#[test]
+fn renders_help_with_custom_renderer() {
+    let output = Rendered::run_from(["help"]).output;
+    assert!(output.contains("Commands:\n- list | Lists the files\n"));
+
+    let output = Rendered::run_from(["list", "-h"]).output;
+    assert!(output.contains("- --all | -a | <true|false> | Include hidden files\n"));
+}
+
+/// Prints the color of each cell instead of its text
+struct Colors;
+
+impl argster::help::HelpRenderer for Colors {
+    fn print_table(&self, out: &mut argster::term::Writer<'_>, rows: &[Vec<argster::help::Cell>]) {
+        use std::io::Write;
+
+        for row in rows {
+            let colors = row.iter().map(|cell| format!("{:?}", cell.style.fg)).collect::<Vec<_>>();
+            _ = writeln!(out, "{}", colors.join(" "));
+        }
+    }
+}
+
+fn light_theme() -> argster::style::Theme {
+    use argster::style::{Color, Style, Theme};
+
+    Theme {
+        command: Style::PLAIN.bold().fg(Color::Blue),
+        option: Style::PLAIN.fg(Color::Magenta),
+        ..Theme::default()
+    }
+}
+
+struct Themed;
+
+#[command(theme = light_theme(), renderer = Colors)]
+impl Themed {
+    /// Builds the project
+    /// # Args
+    /// --release Build with optimizations
+    fn build(release: bool) {
+        let _ = release;
+    }
+}
+
+#[test]
+fn styles_help_with_theme() {
+    let output = Themed::run_from(["help"]).output;
+    assert!(output.contains("Some(Blue) None\n"));
+
+    let output = Themed::run_from(["build", "-h"]).output;
+    assert!(output.contains("Some(Magenta) Some(Magenta) Some(BrightBlack) None\n"));
+}
+
+/// Records the color and the color choice of every painted fence
+#[derive(Default)]
+struct Painter {
+    painted: Vec<(Option<String>, argster::term::ColorChoice)>,
+}
+
+#[command]
+impl Painter {
+    /// Paints the fence
+    /// # Args
+    /// input The fence to paint
+    /// --color The color to paint it
+    fn paint(&mut self, input: String, color: Option<String>) {
+        let _ = input;
+        self.painted.push((color, argster::term::color_choice()));
+    }
+}
+
+#[test]
+fn takes_color_before_the_command() {
+    use argster::term::ColorChoice;
+
+    let mut painter = Painter::default();
+
+    let outcome = painter.run_from(["--color", "always", "paint", "fence"]);
+    assert!(outcome.is_success());
+    assert!(outcome.color);
+
+    let outcome = painter.run_from(["--color=never", "paint", "fence"]);
+    assert!(outcome.is_success());
+    assert!(!outcome.color);
+
+    // After the command it's the command's own option
+    let outcome = painter.run_from(["paint", "fence", "--color", "red"]);
+    assert!(outcome.is_success());
+    assert!(!outcome.color);
+
+    let outcome = painter.run_from(["--no-pager", "--color=always", "paint", "fence", "--color", "blue"]);
+    assert!(outcome.is_success());
+    assert!(outcome.color);
+    assert!(Painter::try_parse_from(["--no-pager=false", "paint", "fence"]).is_ok());
+
+    assert_eq!(
+        painter.painted,
+        [
+            (None, ColorChoice::Always),
+            (None, ColorChoice::Never),
+            (Some("red".to_string()), ColorChoice::Auto),
+            (Some("blue".to_string()), ColorChoice::Always),
+        ]
+    );
+    assert_eq!(argster::term::color_choice(), ColorChoice::Auto);
+
+    let outcome = painter.run_from(["--color", "blue", "paint", "fence"]);
+    assert_eq!(outcome.exit_code, 2);
+    assert!(outcome.output.contains("Expected color to be of type auto|always|never, but found blue"));
+}
@@ -0,0 +1,5 @@
+#[test]
+fn reports_conflicts_with_builtins() {
+    let tests = trybuild::TestCases::new();
+    tests.compile_fail("tests/ui/*.rs");
+}
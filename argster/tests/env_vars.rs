@@ -0,0 +1,38 @@
+use argster::command;
+
+struct Compressor;
+
+#[command]
+impl Compressor {
+    /// Compresses a file
+    /// # Args
+    /// input The file to compress
+    /// --threads The number of threads
+    fn compress(input: String, #[command(env = "ARGSTER_TEST_THREADS", default = "1")] threads: u32) {
+        let _ = (input, threads);
+    }
+}
+
+fn uses_env_vars() {
+    let Ok(CompressorCommand::Compress { threads, .. }) = Compressor::try_parse_from(["compress", "a.txt"]) else {
+        panic!("expected compress");
+    };
+    assert_eq!(threads, 4);
+
+    let Ok(CompressorCommand::Compress { threads, .. }) =
+        Compressor::try_parse_from(["compress", "a.txt", "--threads", "2"])
+    else {
+        panic!("expected compress");
+    };
+    assert_eq!(threads, 2);
+
+    let long = Compressor::run_from(["compress", "--help"]).output;
+    assert!(long.contains("[default: 1] [env: ARGSTER_TEST_THREADS=4]"));
+}
+
+fn main() {
+    // No other threads are running yet, so changing the environment is sound
+    std::env::set_var("ARGSTER_TEST_THREADS", "4");
+
+    uses_env_vars();
+}
@@ -0,0 +1,11 @@
+use argster::command;
+
+struct App;
+
+#[command]
+impl App {
+    /// Prints our own help
+    fn help() {}
+}
+
+fn main() {}
@@ -0,0 +1,15 @@
+use argster::command;
+
+struct App;
+
+#[command]
+impl App {
+    /// Shows a topic
+    /// # Args
+    /// --help The topic to show
+    fn show(help: String) {
+        let _ = help;
+    }
+}
+
+fn main() {}
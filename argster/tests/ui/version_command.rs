@@ -0,0 +1,11 @@
+use argster::command;
+
+struct App;
+
+#[command]
+impl App {
+    /// Prints our own version
+    fn version() {}
+}
+
+fn main() {}